@@ -0,0 +1,379 @@
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    error::WeatherError,
+    http::HttpResponse,
+    types::{usage_types::KeyCooldowns, user_settings::ApiSetting},
+};
+
+/// Environment variable that overrides every other API key source.
+pub const API_KEY_ENV_NAME: &str = "WEATHER_CLI_API_KEY";
+
+/// Where the API key was read from.
+///
+/// Sources are tried in the following order:
+/// 1. `WEATHER_CLI_API_KEY` environment variable
+/// 2. `key_command` (its stdout is used)
+/// 3. `key_file`
+/// 4. `encrypted_key` (decrypted with a passphrase)
+/// 5. `key` stored in the API setting file
+///
+/// Named keys (`keys`) are tried after them when a key is rejected or rate limited.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiKeySource {
+    Environment,
+    Command(String),
+    File(String),
+    Encrypted,
+    Setting,
+    Named(String),
+}
+
+impl fmt::Display for ApiKeySource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiKeySource::Environment => write!(f, "environment variable ({})", API_KEY_ENV_NAME),
+            ApiKeySource::Command(command) => write!(f, "key command ({})", command),
+            ApiKeySource::File(path) => write!(f, "key file ({})", path),
+            ApiKeySource::Encrypted => f.write_str("encrypted API setting file"),
+            ApiKeySource::Setting => f.write_str("API setting file"),
+            ApiKeySource::Named(name) => write!(f, "named key ({})", name),
+        }
+    }
+}
+
+/// An API key and its source.
+pub struct ResolvedApiKey {
+    pub key: String,
+    pub source: ApiKeySource,
+}
+
+impl ResolvedApiKey {
+    /// Name of the key for cool-down periods.
+    /// Keys from sources other than `keys` are called `primary`.
+    pub fn name(&self) -> &str {
+        match &self.source {
+            ApiKeySource::Named(name) => name,
+            _ => PRIMARY_KEY_NAME,
+        }
+    }
+}
+
+/// Name of the key resolved from the sources other than `keys`.
+pub const PRIMARY_KEY_NAME: &str = "primary";
+
+/// Runs a key command and returns its trimmed stdout.
+fn run_key_command(command: &str) -> Result<String> {
+    use std::process::{Command, Stdio};
+
+    let output = if cfg!(windows) {
+        Command::new("cmd")
+            .args(["/C", command])
+            .stderr(Stdio::inherit())
+            .output()
+    } else {
+        Command::new("sh")
+            .args(["-c", command])
+            .stderr(Stdio::inherit())
+            .output()
+    }
+    .context(format!("Failed to run the key command: {}", command))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "The key command failed ({}): {}",
+            output.status,
+            command
+        ));
+    }
+
+    Ok(String::from_utf8(output.stdout)
+        .context("The key command printed invalid UTF-8.")?
+        .trim()
+        .to_string())
+}
+
+/// Reads a key file. On Unix, files readable by other users are refused.
+fn read_key_file(path: &str) -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(path)
+            .context(format!("Failed to read the key file: {}", path))?
+            .permissions()
+            .mode()
+            & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(anyhow!(
+                "The key file is accessible by other users ({:o}). Please run `chmod 600 {}`.",
+                mode,
+                path
+            ));
+        }
+    }
+
+    Ok(std::fs::read_to_string(path)
+        .context(format!("Failed to read the key file: {}", path))?
+        .trim()
+        .to_string())
+}
+
+/// Returns the API key of the environment variable, unless it's empty.
+pub fn environment_api_key() -> Option<String> {
+    std::env::var(API_KEY_ENV_NAME)
+        .ok()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+/// Resolves the API key from its sources.
+/// `environment_key` is the key of the environment variable. (see `environment_api_key`)
+pub fn resolve_api_key(
+    api_setting: &ApiSetting,
+    environment_key: Option<&str>,
+) -> Result<ResolvedApiKey> {
+    let resolved = if let Some(key) = environment_key {
+        ResolvedApiKey {
+            key: key.to_string(),
+            source: ApiKeySource::Environment,
+        }
+    } else if let Some(command) = &api_setting.key_command {
+        ResolvedApiKey {
+            key: run_key_command(command)?,
+            source: ApiKeySource::Command(command.clone()),
+        }
+    } else if let Some(path) = &api_setting.key_file {
+        ResolvedApiKey {
+            key: read_key_file(path)?,
+            source: ApiKeySource::File(path.clone()),
+        }
+    } else if let Some(encrypted_key) = &api_setting.encrypted_key {
+        use crate::key_encryption::{decrypt_api_key, read_passphrase};
+
+        ResolvedApiKey {
+            key: decrypt_api_key(encrypted_key, &read_passphrase(false)?)?,
+            source: ApiKeySource::Encrypted,
+        }
+    } else if let Some(key) = &api_setting.key {
+        ResolvedApiKey {
+            key: key.clone(),
+            source: ApiKeySource::Setting,
+        }
+    } else {
+        return Err(WeatherError::ConfigMissing(
+            "No API key is configured. Please make sure to setup your API key.".to_string(),
+        )
+        .into());
+    };
+
+    if resolved.key.is_empty() {
+        return Err(WeatherError::ConfigMissing(format!(
+            "The API key from the {} is empty.",
+            resolved.source
+        ))
+        .into());
+    }
+
+    crate::redaction::register_secret(&resolved.key);
+    Ok(resolved)
+}
+
+/// Resolves every API key in the order they are tried.
+///
+/// The environment variable overrides every other key.
+/// Otherwise the primary key comes first, followed by the named keys.
+/// When the primary key fails (ex. `key-command` exits with an error),
+/// the named keys are used with a warning. Without named keys, it's an error.
+pub fn resolve_api_keys(
+    api_setting: &ApiSetting,
+    environment_key: Option<&str>,
+) -> Result<Vec<ResolvedApiKey>> {
+    let has_primary_key = api_setting.key_command.is_some()
+        || api_setting.key_file.is_some()
+        || api_setting.encrypted_key.is_some()
+        || api_setting.key.is_some();
+
+    if environment_key.is_some() {
+        return Ok(vec![resolve_api_key(api_setting, environment_key)?]);
+    }
+
+    let mut keys = vec![];
+    if has_primary_key || api_setting.keys.is_empty() {
+        match resolve_api_key(api_setting, None) {
+            Ok(primary_key) => keys.push(primary_key),
+            Err(e) if !api_setting.keys.is_empty() => eprintln!(
+                "WARNING: The primary API key is unavailable. Trying the named keys. ({})",
+                crate::redaction::redact(&format!("{:#}", e))
+            ),
+            Err(e) => return Err(e),
+        }
+    }
+    keys.extend(named_api_keys(api_setting));
+
+    Ok(keys)
+}
+
+/// Returns the named keys of an API setting.
+fn named_api_keys(api_setting: &ApiSetting) -> impl Iterator<Item = ResolvedApiKey> + '_ {
+    api_setting.keys.iter().map(|named_key| {
+        crate::redaction::register_secret(&named_key.key);
+        ResolvedApiKey {
+            key: named_key.key.clone(),
+            source: ApiKeySource::Named(named_key.name.clone()),
+        }
+    })
+}
+
+/// Reads the API setting file and resolves the API key tried first.
+pub fn read_api_key() -> Result<ResolvedApiKey> {
+    let mut keys = read_api_keys()?;
+    Ok(keys.remove(0))
+}
+
+/// Reads the API setting file and resolves every API key.
+pub fn read_api_keys() -> Result<Vec<ResolvedApiKey>> {
+    use crate::{constants::API_JSON_NAME, read_json_file};
+
+    let api_setting = read_json_file::<ApiSetting>(API_JSON_NAME)?;
+    resolve_api_keys(&api_setting, environment_api_key().as_deref())
+}
+
+/// Orders keys for a request at `now`. (UNIX timestamp)
+///
+/// Keys in a cool-down period are skipped.
+/// When every key is cooling down, only the one that recovers first is returned.
+pub fn order_api_keys(
+    keys: Vec<ResolvedApiKey>,
+    cooldowns: &KeyCooldowns,
+    now: i64,
+) -> Vec<ResolvedApiKey> {
+    let cooldown_end = |key: &ResolvedApiKey| cooldowns.until.get(key.name()).copied();
+
+    let (available, cooling_down): (Vec<_>, Vec<_>) = keys
+        .into_iter()
+        .partition(|key| cooldown_end(key).is_none_or(|until| until <= now));
+    if !available.is_empty() {
+        return available;
+    }
+
+    cooling_down
+        .into_iter()
+        .min_by_key(|key| cooldown_end(key))
+        .into_iter()
+        .collect()
+}
+
+/// Returns how long a key should cool down after a response, in seconds.
+fn response_cooldown(response: &HttpResponse) -> Option<i64> {
+    use crate::{
+        api_error_from_response,
+        constants::{INVALID_KEY_COOLDOWN_SECONDS, RATE_LIMITED_KEY_COOLDOWN_SECONDS},
+    };
+
+    let body = serde_json::from_str(&response.body).unwrap_or(serde_json::Value::Null);
+    match api_error_from_response(response.status, &body)? {
+        WeatherError::InvalidApiKey => Some(INVALID_KEY_COOLDOWN_SECONDS),
+        WeatherError::RateLimited { .. } => Some(RATE_LIMITED_KEY_COOLDOWN_SECONDS),
+        _ => None,
+    }
+}
+
+/// Sends a request with the first usable API key. `make_url` builds the URL with a key.
+///
+/// A key that is rejected (401) or rate limited (429) is put in a cool-down period,
+/// and the request is sent again with the next key.
+/// The response of the last key is returned when every key fails.
+pub async fn get_response_with_api_key(
+    endpoint: &str,
+    make_url: impl Fn(&str) -> String,
+) -> Result<HttpResponse> {
+    use chrono::Utc;
+
+    use crate::{
+        constants::KEY_COOLDOWN_JSON_NAME, http::get_response, read_json_file, write_json_file,
+    };
+
+    let mut cooldowns = read_json_file::<KeyCooldowns>(KEY_COOLDOWN_JSON_NAME).unwrap_or_default();
+    let keys = order_api_keys(read_api_keys()?, &cooldowns, Utc::now().timestamp());
+    let cooldowns_before = cooldowns.until.clone();
+
+    let mut last_response = None;
+    for (index, api_key) in keys.iter().enumerate() {
+        // A rate limited key is only retried when there's no other key to rotate to.
+        let has_next_key = index + 1 < keys.len();
+        let response = get_response(endpoint, make_url(&api_key.key), !has_next_key).await?;
+
+        let Some(cooldown) = response_cooldown(&response) else {
+            cooldowns.until.remove(api_key.name());
+            last_response = Some(response);
+            break;
+        };
+        cooldowns.until.insert(
+            api_key.name().to_string(),
+            Utc::now().timestamp() + cooldown,
+        );
+        if has_next_key {
+            eprintln!(
+                "NOTE: API key '{}' failed with HTTP {}. Trying the next key.",
+                api_key.name(),
+                response.status
+            );
+        }
+        last_response = Some(response);
+    }
+
+    if cooldowns.until != cooldowns_before {
+        write_json_file(KEY_COOLDOWN_JSON_NAME, &cooldowns)?;
+    }
+
+    last_response.ok_or_else(|| anyhow!("No API key is available."))
+}
+
+/// Describes the primary API key without resolving it,
+/// so no key command runs and no passphrase is asked.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::{api_key::describe_primary_key, types::user_settings::ApiSetting};
+/// let api_setting = ApiSetting {
+///     key_file: Some(String::from("/home/user/.owm-key")),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     describe_primary_key(&api_setting).unwrap(),
+///     "key file (/home/user/.owm-key)"
+/// );
+/// assert_eq!(describe_primary_key(&ApiSetting::default()), None);
+/// ```
+pub fn describe_primary_key(api_setting: &ApiSetting) -> Option<String> {
+    if let Some(command) = &api_setting.key_command {
+        Some(format!("key command ({})", command))
+    } else if let Some(path) = &api_setting.key_file {
+        Some(format!("key file ({})", path))
+    } else if api_setting.encrypted_key.is_some() {
+        Some("encrypted".to_string())
+    } else {
+        api_setting.key.as_deref().map(mask_api_key)
+    }
+}
+
+/// Masks an API key except for the last 4 characters.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::api_key::mask_api_key;
+/// assert_eq!(mask_api_key("0123456789abcdef"), "************cdef");
+/// assert_eq!(mask_api_key("abc"), "***");
+/// ```
+pub fn mask_api_key(key: &str) -> String {
+    let length = key.chars().count();
+    if length <= 4 {
+        return "*".repeat(length);
+    }
+
+    let visible: String = key.chars().skip(length - 4).collect();
+    format!("{}{}", "*".repeat(length - 4), visible)
+}
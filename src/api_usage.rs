@@ -1,120 +1,404 @@
 use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 
 use crate::{
+    api_key::get_response_with_api_key,
+    clock::Clock,
     constants::GEOLOCATION_API_URL,
-    types::user_settings::{City, Units, UserSetting},
-    ErrorMessageType,
+    icons::IconStyle,
+    theme::{ColorChoice, Painter},
+    types::{
+        cache_types::CacheEntry,
+        response_types::{Weather, WeatherApiResponse},
+        user_settings::{City, Units, UserSetting},
+    },
 };
 
-enum EventInfo<T: TimeZone> {
-    Sunrise(DateTime<T>),
-    Sunset(DateTime<T>),
+/// Output format of the program.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// JSON objects, including errors.
+    Json,
 }
-impl<T: TimeZone> std::fmt::Display for EventInfo<T>
-where
-    T::Offset: std::fmt::Display,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let local_time = match self {
-            EventInfo::Sunrise(sunrise_time) => sunrise_time.format("Sunrise: %I:%M %p"),
-            EventInfo::Sunset(sunset_time) => sunset_time.format("Sunset: %I:%M %p"),
-        };
-        write!(f, "{}", local_time)
-    }
+
+/// How much of the weather information `check` prints.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum DetailLevel {
+    /// Temperature and conditions only.
+    Brief,
+    /// Also wind, humidity, pressure, precipitation and sunrise/sunset.
+    #[default]
+    Normal,
+    /// Also visibility, clouds and sea/ground level pressure.
+    Full,
 }
 
-/// Returns sunrise and sunset time.
-/// The first element in the returning array should be upcoming one.
-/// ex) It shows sunset time before sunrise time in the afternoon.
-fn convert_utc_to_local_time(
-    sunrise_timestamp: i64,
-    sunset_timestamp: i64,
-    timezone: i32,
-) -> Result<(EventInfo<FixedOffset>, EventInfo<FixedOffset>)> {
-    let timezone = FixedOffset::east_opt(timezone).context("Failed to read timezone value.")?;
-
-    let current_time = Utc::now().with_timezone(&timezone);
-    let sunrise = DateTime::<Utc>::from_timestamp(sunrise_timestamp, 0)
-        .context("Failed to read sunrise time.")?
-        .with_timezone(&timezone);
-    let sunset = DateTime::<Utc>::from_timestamp(sunset_timestamp, 0)
-        .context("Failed to read sunset Time.")?
-        .with_timezone(&timezone);
-
-    // The first element should be the next upcoming event.
-    if current_time < sunrise {
-        Ok((EventInfo::Sunrise(sunrise), EventInfo::Sunset(sunset)))
-    } else if current_time < sunset {
-        Ok((EventInfo::Sunset(sunset), EventInfo::Sunrise(sunrise)))
-    } else {
-        Ok((EventInfo::Sunrise(sunrise), EventInfo::Sunset(sunset)))
-    }
+/// Returns the weather data of a cached entry without the network, with a notice of its age.
+/// Fails with `WeatherError::Network` when nothing is cached.
+pub fn offline_weather_data(
+    cached_entry: Option<CacheEntry>,
+) -> Result<(WeatherApiResponse, String)> {
+    use crate::{cache::format_age, error::WeatherError};
+
+    let entry = cached_entry.ok_or_else(|| WeatherError::Network {
+        message:
+            "No cached weather data is available offline. Please run 'check' while online first."
+                .to_string(),
+        status: None,
+    })?;
+    let notice = format!(
+        "Offline: showing data from {} ago.",
+        format_age(entry.age())
+    );
+
+    Ok((parse_weather_response(200, &entry.body)?, notice))
 }
 
-/// Returns a response from the given URL.
-async fn get_response(url: String) -> Result<String> {
-    let resp = reqwest::get(&url).await?;
-    let text = resp.text().await?;
-    Ok(text)
+/// Returns the weather data of a stale cached entry after a failed request, with a notice.
+///
+/// Only retryable failures (network errors, rate limits and the usage budget) fall back
+/// to the cache. Other errors, such as an invalid API key, are returned as they are.
+pub fn stale_weather_data(
+    error: anyhow::Error,
+    cached_entry: Option<CacheEntry>,
+) -> Result<(WeatherApiResponse, String)> {
+    use crate::{cache::format_age, error::WeatherError};
+
+    let is_retryable = error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<WeatherError>())
+        .any(WeatherError::is_retryable);
+    let Some(entry) = cached_entry.filter(|_| is_retryable) else {
+        return Err(error);
+    };
+
+    let notice = format!(
+        "Showing cached data from {} ago. ({})",
+        format_age(entry.age()),
+        error
+    );
+    Ok((parse_weather_response(200, &entry.body)?, notice))
 }
 
-/// Prints weather information from the API.
-pub async fn print_weather_information() -> Result<()> {
-    use crate::{
-        constants::{API_JSON_NAME, USER_SETTING_JSON_NAME, WEATHER_API_URL},
-        read_json_file, read_json_response, replace_url_placeholders,
-        types::{response_types::WeatherApiResponse, user_settings::ApiSetting},
-        URLPlaceholder,
+/// Formats the weather conditions in one line. (ex. "Mist (mist), Rain (light rain)")
+/// The API may send more than one condition, or none.
+pub fn format_conditions(weather: &[Weather], icon_style: IconStyle, painter: &Painter) -> String {
+    use crate::icons::condition_icon;
+
+    weather
+        .iter()
+        .map(|weather| {
+            let condition = format!("{} ({})", weather.main, weather.description);
+            let condition = painter.condition(&condition, weather.category());
+            match condition_icon(weather, icon_style) {
+                Some(icon) => format!("{} {}", icon, condition),
+                None => condition,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Options for the `check` command.
+#[derive(Default)]
+pub struct CheckOptions {
+    /// Always request fresh data, bypassing the response cache.
+    pub no_cache: bool,
+    /// Render from the response cache without any network request.
+    pub offline: bool,
+    pub output: OutputFormat,
+    pub detail: DetailLevel,
+    /// Icon style. Falls back to the `icons` setting.
+    pub icons: Option<IconStyle>,
+    /// When to color the text output.
+    pub color: ColorChoice,
+}
+
+/// Checks whether the host of the given URL can be resolved.
+async fn is_network_available(url: &str) -> bool {
+    use std::time::Duration;
+
+    let Some(host) = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| format!("{}:443", host)))
+    else {
+        return false;
     };
 
-    let api_json_data = read_json_file::<ApiSetting>(API_JSON_NAME)?;
-    let setting_json_data = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    matches!(
+        tokio::time::timeout(Duration::from_secs(2), tokio::net::lookup_host(host)).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Parses a weather API response body.
+/// Cached responses are successful ones, so they use `200` as the status.
+fn parse_weather_response(status: u16, response: &str) -> Result<WeatherApiResponse> {
+    use crate::read_json_response;
+
+    read_json_response::<WeatherApiResponse>(status, response, "WeatherApiResponse")
+}
+
+/// Loads weather data from the response cache or the API.
+/// Also returns a notice when the data comes from an outdated cache.
+async fn load_weather_data(
+    setting: &UserSetting,
+    options: &CheckOptions,
+) -> Result<(WeatherApiResponse, Option<String>)> {
+    use crate::{
+        cache::{read_cache_entry, write_cache_entry, CacheKey},
+        constants::{DEFAULT_CACHE_TTL_SECONDS, WEATHER_API_URL},
+        error::WeatherError,
+        http::uses_proxy,
+        make_weather_api_url,
+    };
 
-    let url = match (&setting_json_data.city, &setting_json_data.units) {
-        (Some(city), Some(unit)) => {
-            let units = unit.to_string();
-
-            replace_url_placeholders(
-                WEATHER_API_URL,
-                &[
-                    URLPlaceholder {
-                        placeholder: "{LAT_VALUE}".to_string(),
-                        value: city.lat.to_string(),
-                    },
-                    URLPlaceholder {
-                        placeholder: "{LON_VALUE}".to_string(),
-                        value: city.lon.to_string(),
-                    },
-                    URLPlaceholder {
-                        placeholder: "{API_KEY}".to_string(),
-                        value: api_json_data.key,
-                    },
-                    URLPlaceholder {
-                        placeholder: "{UNIT}".to_string(),
-                        value: units,
-                    },
-                ],
+    let (city, units) = setting
+        .city
+        .as_ref()
+        .zip(setting.units.as_ref())
+        .ok_or_else(|| {
+            WeatherError::ConfigMissing(
+                "Failed to read user setting! Please run 'set-location' command to configure settings."
+                    .to_string(),
             )
+        })?;
+    let cache_key = CacheKey {
+        provider: "openweather",
+        endpoint: "weather",
+        lat: city.lat,
+        lon: city.lon,
+        units,
+    };
+
+    // Offline mode never attempts a request.
+    let offline = options.offline;
+    let cached_entry = if options.no_cache && !offline {
+        None
+    } else {
+        read_cache_entry(&cache_key).unwrap_or(None)
+    };
+    let cache_ttl = setting.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL_SECONDS);
+
+    if let Some(entry) = &cached_entry {
+        if entry.is_fresh(cache_ttl) && !offline {
+            return Ok((parse_weather_response(200, &entry.body)?, None));
+        }
+    }
+
+    // Behind a proxy, the API host may not be resolvable locally.
+    let network_available =
+        uses_proxy(&setting.network) || is_network_available(WEATHER_API_URL).await;
+    if offline || !network_available {
+        let (data, notice) = offline_weather_data(cached_entry)?;
+        return Ok((data, Some(notice)));
+    }
+
+    // Request a new response and fall back to the stale cached one on failure.
+    let fetched = match get_response_with_api_key("weather", |api_key| {
+        make_weather_api_url(city.lat, city.lon, api_key, &units.to_string())
+    })
+    .await
+    {
+        Ok(response) => parse_weather_response(response.status, &response.body)
+            .map(|data| (response.body, data)),
+        Err(e) => Err(e),
+    };
+
+    match (fetched, cached_entry) {
+        (Ok((response, data)), _) => {
+            write_cache_entry(&cache_key, &response)?;
+            Ok((data, None))
         }
-        _ => {
-            return Err(anyhow!(
-            "Failed to read user setting! Please run 'set-location' command to configure settings."
-        ))
+        (Err(e), cached_entry) => {
+            let (data, notice) = stale_weather_data(e, cached_entry)?;
+            Ok((data, Some(notice)))
         }
+    }
+}
+
+/// Returns weather information as the JSON object of `--output json`.
+pub fn weather_json(
+    setting: &UserSetting,
+    response_data: &WeatherApiResponse,
+    cache_notice: Option<String>,
+    clock: &impl Clock,
+) -> Result<serde_json::Value> {
+    use crate::{
+        daylight::{upcoming_sun_events, Daylight, EventInfo},
+        icons::is_night,
+        meteo::DerivedQuantities,
+        moon::MoonInfo,
+        units::{compass_direction, temperature_to_kelvin, wind_speed_to_mps, UnitSystem},
     };
 
-    let response = get_response(url).await?;
-    let response_data = read_json_response::<WeatherApiResponse>(
-        &response,
-        ErrorMessageType::ApiResponseRead,
-        "WeatherApiResponse",
+    let city = setting
+        .city
+        .as_ref()
+        .context("Failed to read city setting data.")?;
+    let units = setting
+        .units
+        .as_ref()
+        .context("Failed to read unit setting data.")?;
+    let unit_system = UnitSystem::new(units, &setting.display_units);
+    // Conversions through SI units leave floating-point noise. (ex. 11.339999999999975)
+    let round = |value: f64| (value * 100.0).round() / 100.0;
+    let temperature = |value: f64| {
+        round(
+            unit_system
+                .temperature
+                .from_kelvin(temperature_to_kelvin(value, units)),
+        )
+    };
+    let wind_speed = |value: f64| round(unit_system.wind.from_mps(wind_speed_to_mps(value, units)));
+    let derived = DerivedQuantities::new(
+        temperature_to_kelvin(response_data.main.temp, units),
+        response_data.main.humidity as f64,
+        wind_speed_to_mps(response_data.wind.speed, units),
+    );
+    let from_kelvin = |kelvin: f64| round(unit_system.temperature.from_kelvin(kelvin));
+    let pressure = |value: u32| round(unit_system.pressure.from_hpa(value as f64));
+    let precipitation = |one_hour: Option<f64>, three_hours: Option<f64>| {
+        serde_json::json!({
+            "1h": one_hour.map(|amount| round(unit_system.precipitation.from_mm(amount))),
+            "3h": three_hours.map(|amount| round(unit_system.precipitation.from_mm(amount))),
+        })
+    };
+    let daylight = upcoming_sun_events(
+        response_data.sys.sunrise as i64,
+        response_data.sys.sunset as i64,
+        response_data.timezone,
+        response_data.coord.lat,
+        response_data.coord.lon,
+        clock,
     )?;
+    let moon = MoonInfo::new(clock.now());
+    // The upcoming sunrise and sunset. Both are `null` in polar day and night,
+    // and one of them is when polar day or night begins after the other.
+    let (mut sunrise, mut sunset) = (None, None);
+    let polar = match &daylight {
+        Daylight::Events(upcoming, following) => {
+            for event in std::iter::once(upcoming).chain(following) {
+                match event {
+                    EventInfo::Sunrise(time) => sunrise = Some(time.to_rfc3339()),
+                    EventInfo::Sunset(time) => sunset = Some(time.to_rfc3339()),
+                }
+            }
+            None
+        }
+        Daylight::PolarDay => Some("day"),
+        Daylight::PolarNight => Some("night"),
+    };
+
+    let conditions: Vec<serde_json::Value> = response_data
+        .weather
+        .iter()
+        .map(|weather| {
+            serde_json::json!({
+                "id": weather.id,
+                "category": weather.category().map(|category| category.name()),
+                "icon": weather.icon,
+                "night": is_night(weather),
+                "main": weather.main,
+                "description": weather.description,
+            })
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "city": {
+            "name": city.name,
+            "country": city.country,
+            "lat": city.lat,
+            "lon": city.lon,
+        },
+        "units": units.to_string(),
+        "display_units": {
+            "temperature": unit_system.temperature.symbol(),
+            "wind": unit_system.wind.symbol(),
+            "pressure": unit_system.pressure.symbol(),
+            "precipitation": unit_system.precipitation.symbol(),
+            "visibility": unit_system.visibility.symbol(),
+        },
+        "temperature": {
+            "current": temperature(response_data.main.temp),
+            "min": temperature(response_data.main.temp_min),
+            "max": temperature(response_data.main.temp_max),
+            "feels_like": response_data.main.feels_like.map(temperature),
+        },
+        "conditions": conditions,
+        "wind": {
+            "speed": wind_speed(response_data.wind.speed),
+            "gust": response_data.wind.gust.map(wind_speed),
+            "deg": response_data.wind.deg,
+            "direction": response_data.wind.deg.map(|degrees| compass_direction(degrees as f64)),
+        },
+        "derived": {
+            "dew_point": from_kelvin(derived.dew_point),
+            "heat_index": derived.heat_index.map(from_kelvin),
+            "wind_chill": derived.wind_chill.map(from_kelvin),
+            "humidex": from_kelvin(derived.humidex),
+            "apparent_temperature": from_kelvin(derived.apparent_temperature),
+        },
+        "humidity": response_data.main.humidity,
+        "pressure": pressure(response_data.main.pressure),
+        "sea_level_pressure": response_data.main.sea_level.map(pressure),
+        "ground_level_pressure": response_data.main.grnd_level.map(pressure),
+        "visibility": response_data
+            .visibility
+            .map(|visibility| round(unit_system.visibility.from_meters(visibility as f64))),
+        "clouds": response_data.clouds.all,
+        "rain": response_data.rain.as_ref().map(|rain| precipitation(rain.one_h, rain.three_h)),
+        "snow": response_data.snow.as_ref().map(|snow| precipitation(snow.one_h, snow.three_h)),
+        "sunrise": sunrise,
+        "sunset": sunset,
+        "polar": polar,
+        "moon": {
+            "phase": moon.phase.to_string(),
+            "illumination": round(moon.illumination * 100.0),
+            "age": round(moon.age),
+        },
+        "notice": cache_notice,
+    }))
+}
+
+/// Prints weather information from the API.
+pub async fn print_weather_information(options: &CheckOptions) -> Result<()> {
+    use std::io::IsTerminal;
+
+    use crate::{
+        clock::SystemClock,
+        constants::USER_SETTING_JSON_NAME,
+        daylight::{upcoming_sun_events, Daylight, EventInfo},
+        icons::ascii_art,
+        meteo::DerivedQuantities,
+        moon::{moon_rise_set, MoonInfo},
+        read_json_file,
+        theme::Theme,
+        time_display::TimeFormatter,
+        units::{compass_direction, temperature_to_kelvin, wind_speed_to_mps, UnitSystem},
+    };
+
+    let setting_json_data = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    let (response_data, cache_notice) = load_weather_data(&setting_json_data, options).await?;
 
-    let upcoming_event = convert_utc_to_local_time(
+    let clock = SystemClock;
+    if options.output == OutputFormat::Json {
+        let output = weather_json(&setting_json_data, &response_data, cache_notice, &clock)?;
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let daylight = upcoming_sun_events(
         response_data.sys.sunrise as i64,
         response_data.sys.sunset as i64,
         response_data.timezone,
+        response_data.coord.lat,
+        response_data.coord.lon,
+        &clock,
     )?;
 
     // Print the weather information.
@@ -123,10 +407,11 @@ pub async fn print_weather_information() -> Result<()> {
         Example Output:
         ```
         Toronto (CA)
-        9.57° / Clouds (overcast clouds)
-        H: 9.57°, L: 9.57°
+        9.6°C / Clouds (overcast clouds)
+        H: 9.6°C, L: 9.6°C
+        Feels like: 7.1°C
 
-        - Wind Speed: 4.59 m/s,
+        - Wind: WSW 4.6 m/s, gusts 7.2 m/s,
         - Humidity: 61 %,
         - Pressure: 1017 hPa
         - Sunrise: 06:22 AM
@@ -140,42 +425,227 @@ pub async fn print_weather_information() -> Result<()> {
         let selected_unit = setting_json_data
             .units
             .context("Failed to read unit setting data.")?;
-        let wind_unit = match selected_unit {
-            Units::Standard => "m/s",
-            Units::Metric => "m/s",
-            Units::Imperial => "mph",
+        let unit_system = UnitSystem::new(&selected_unit, &setting_json_data.display_units);
+        let painter = Painter {
+            theme: Theme::load(setting_json_data.theme.unwrap_or_default())?,
+            enabled: options.color.is_enabled(std::io::stdout().is_terminal()),
+        };
+        let format_kelvin =
+            |kelvin: f64| painter.temperature(&unit_system.temperature.format(kelvin), kelvin);
+        let temperature = |value: f64| format_kelvin(temperature_to_kelvin(value, &selected_unit));
+
+        let wind_speed = |value: f64| {
+            unit_system
+                .wind
+                .format(wind_speed_to_mps(value, &selected_unit))
         };
+        let precipitation = |one_hour: Option<f64>, three_hours: Option<f64>| {
+            let totals: Vec<String> = [(one_hour, "1h"), (three_hours, "3h")]
+                .into_iter()
+                .filter_map(|(amount, period)| {
+                    amount.map(|amount| {
+                        format!("{} ({})", unit_system.precipitation.format(amount), period)
+                    })
+                })
+                .collect();
+            (!totals.is_empty()).then(|| totals.join(", "))
+        };
+
+        let icon_style = options
+            .icons
+            .or(setting_json_data.icons)
+            .unwrap_or_default();
+        let conditions = format_conditions(&response_data.weather, icon_style, &painter);
+
+        if let Some(notice) = cache_notice {
+            println!("\n* {}", painter.warning(&notice));
+        }
+
+        let mut header = vec![
+            painter.heading(&format!(
+                "{} ({})",
+                selected_city.name, selected_city.country
+            )),
+            match conditions.is_empty() {
+                true => temperature(response_data.main.temp),
+                false => format!("{} / {}", temperature(response_data.main.temp), conditions),
+            },
+        ];
+        if options.detail != DetailLevel::Brief {
+            header.push(format!(
+                "H: {max}, L: {min}",
+                max = temperature(response_data.main.temp_max),
+                min = temperature(response_data.main.temp_min)
+            ));
+            if let Some(feels_like) = response_data.main.feels_like {
+                header.push(format!("Feels like: {}", temperature(feels_like)));
+            }
+        }
+
+        // ASCII art is drawn on the left of the header, like wttr.in.
+        if let (IconStyle::Ascii, Some(weather)) = (icon_style, response_data.weather.first()) {
+            let art = ascii_art(weather);
+            let width = art[0].len();
+            header = (0..art.len().max(header.len()))
+                .map(|index| {
+                    let line = header.get(index).map_or("", String::as_str);
+                    let art_line = art.get(index).copied().unwrap_or_default();
+                    format!("{:width$} {}", art_line, line, width = width)
+                        .trim_end()
+                        .to_string()
+                })
+                .collect();
+            while header.last().is_some_and(String::is_empty) {
+                header.pop();
+            }
+        }
 
-        let output_messages = [
-            String::new(),
-            format!("{} ({})", selected_city.name, selected_city.country),
-            format!(
-                "{temp}° / {main} ({description})",
-                temp = response_data.main.temp,
-                main = response_data.weather[0].main,
-                description = response_data.weather[0].description
-            ),
-            format!(
-                "H: {max}°, L: {min}°",
-                max = response_data.main.temp_max,
-                min = response_data.main.temp_min
-            ),
-            format!(
-                "\n- Wind Speed: {speed} {wind_speed_unit},",
-                speed = response_data.wind.speed,
-                wind_speed_unit = wind_unit
-            ),
-            format!(
+        let mut output_messages = vec![String::new()];
+        output_messages.extend(header);
+
+        if options.detail != DetailLevel::Brief {
+            let wind = &response_data.wind;
+            let mut wind_message = match wind.deg {
+                Some(degrees) => format!(
+                    "{} {}",
+                    compass_direction(degrees as f64),
+                    wind_speed(wind.speed)
+                ),
+                None => wind_speed(wind.speed),
+            };
+            if let Some(gust) = wind.gust {
+                wind_message.push_str(&format!(", gusts {}", wind_speed(gust)));
+            }
+            output_messages.push(format!("\n- Wind: {},", wind_message));
+            output_messages.push(format!(
                 "- Humidity: {humidity} %,",
                 humidity = response_data.main.humidity
-            ),
-            format!(
-                "- Pressure: {pressure} hPa",
-                pressure = response_data.main.pressure
-            ),
-            format!("- {}", upcoming_event.0),
-            format!("  ({})", upcoming_event.1),
-        ];
+            ));
+            output_messages.push(format!(
+                "- Pressure: {pressure}",
+                pressure = unit_system
+                    .pressure
+                    .format(response_data.main.pressure as f64)
+            ));
+
+            if let Some(rain) = response_data
+                .rain
+                .as_ref()
+                .and_then(|rain| precipitation(rain.one_h, rain.three_h))
+            {
+                output_messages.push(format!("- Rain: {}", rain));
+            }
+            if let Some(snow) = response_data
+                .snow
+                .as_ref()
+                .and_then(|snow| precipitation(snow.one_h, snow.three_h))
+            {
+                output_messages.push(format!("- Snow: {}", snow));
+            }
+        }
+
+        if options.detail == DetailLevel::Full {
+            if let Some(visibility) = response_data.visibility {
+                output_messages.push(format!(
+                    "- Visibility: {}",
+                    unit_system.visibility.format(visibility as f64)
+                ));
+            }
+            if let Some(clouds) = response_data.clouds.all {
+                output_messages.push(format!("- Clouds: {} %", clouds));
+            }
+            if let Some(sea_level) = response_data.main.sea_level {
+                output_messages.push(format!(
+                    "- Sea level pressure: {}",
+                    unit_system.pressure.format(sea_level as f64)
+                ));
+            }
+            if let Some(ground_level) = response_data.main.grnd_level {
+                output_messages.push(format!(
+                    "- Ground level pressure: {}",
+                    unit_system.pressure.format(ground_level as f64)
+                ));
+            }
+        }
+
+        if options.detail == DetailLevel::Full {
+            let derived = DerivedQuantities::new(
+                temperature_to_kelvin(response_data.main.temp, &selected_unit),
+                response_data.main.humidity as f64,
+                wind_speed_to_mps(response_data.wind.speed, &selected_unit),
+            );
+
+            output_messages.push(format!("- Dew point: {}", format_kelvin(derived.dew_point)));
+            if let Some(heat_index) = derived.heat_index {
+                output_messages.push(format!("- Heat index: {}", format_kelvin(heat_index)));
+            }
+            if let Some(wind_chill) = derived.wind_chill {
+                output_messages.push(format!("- Wind chill: {}", format_kelvin(wind_chill)));
+            }
+            output_messages.push(format!("- Humidex: {}", format_kelvin(derived.humidex)));
+            output_messages.push(format!(
+                "- Apparent temperature: {}",
+                format_kelvin(derived.apparent_temperature)
+            ));
+        }
+
+        if options.detail != DetailLevel::Brief {
+            let time_formatter = TimeFormatter::new(&setting_json_data.time);
+            match daylight {
+                Daylight::Events(upcoming, following) => {
+                    output_messages.push(format!(
+                        "- {}",
+                        upcoming.describe(&time_formatter, Some(clock.now()))
+                    ));
+                    output_messages.push(match (following, upcoming) {
+                        (Some(following), _) => {
+                            format!("  ({})", following.describe(&time_formatter, None))
+                        }
+                        (None, EventInfo::Sunrise(_)) => {
+                            "  (Polar day begins: the sun doesn't set after it.)".to_string()
+                        }
+                        (None, EventInfo::Sunset(_)) => {
+                            "  (Polar night begins: the sun doesn't rise after it.)".to_string()
+                        }
+                    });
+                }
+                Daylight::PolarDay => {
+                    output_messages.push("- Polar day: the sun doesn't set today.".to_string())
+                }
+                Daylight::PolarNight => {
+                    output_messages.push("- Polar night: the sun doesn't rise today.".to_string())
+                }
+            }
+
+            if options.detail == DetailLevel::Full {
+                let now = clock.now();
+                let timezone = FixedOffset::east_opt(response_data.timezone)
+                    .context("Failed to read timezone value.")?;
+                let moon = MoonInfo::new(now);
+                let rise_set = moon_rise_set(
+                    now.with_timezone(&timezone).date_naive(),
+                    timezone,
+                    selected_city.lat,
+                    selected_city.lon,
+                );
+                let moon_time = |time: Option<DateTime<Utc>>| match time {
+                    Some(time) => time_formatter.time(&time.with_timezone(&timezone)),
+                    None => "none today".to_string(),
+                };
+
+                output_messages.push(format!(
+                    "- Moon: {}, {:.0} % illuminated, {:.1} days old",
+                    moon.phase,
+                    moon.illumination * 100.0,
+                    moon.age
+                ));
+                output_messages.push(format!(
+                    "  (Moonrise: {}, Moonset: {})",
+                    moon_time(rise_set.rise),
+                    moon_time(rise_set.set)
+                ));
+            }
+        }
 
         for item in output_messages {
             println!("{}", item);
@@ -220,11 +690,10 @@ fn select_user_preferences(cities: &[City]) -> Result<(String, Units)> {
         let user_input = read_user_input(&["Please select your city."])?;
 
         let parsed_input: usize = user_input.trim().parse()?;
-        if parsed_input > cities.len() {
-            return Err(anyhow!("Invalid city index."));
-        }
-
-        &cities[parsed_input - 1]
+        parsed_input
+            .checked_sub(1)
+            .and_then(|index| cities.get(index))
+            .ok_or_else(|| anyhow!("Invalid city index."))?
     };
 
     let units: Units = {
@@ -257,6 +726,7 @@ fn select_user_preferences(cities: &[City]) -> Result<(String, Units)> {
             country: city.country.clone(),
         }),
         units: Some(units.clone()),
+        ..Default::default()
     };
 
     update_user_settings(&user_setting)?;
@@ -266,65 +736,45 @@ fn select_user_preferences(cities: &[City]) -> Result<(String, Units)> {
 
 /// Selects a city from a list.
 pub async fn search_city(query: &str) -> Result<()> {
-    use serde_json::Value;
-
     use crate::{
-        constants::API_JSON_NAME, get_file_read_error_message, read_json_file,
-        replace_url_placeholders, types::user_settings::ApiSetting, URLPlaceholder,
+        error::WeatherError, read_json_response, replace_url_placeholders, URLPlaceholder,
     };
 
     if query.is_empty() {
         return Err(anyhow!("Query cannot be empty."));
     }
 
-    let api_json_data = read_json_file::<ApiSetting>(API_JSON_NAME)?;
-
-    let url = replace_url_placeholders(
-        GEOLOCATION_API_URL,
-        &[
-            URLPlaceholder {
-                placeholder: "{QUERY}".to_string(),
-                value: query.to_string(),
-            },
-            URLPlaceholder {
-                placeholder: "{API_KEY}".to_string(),
-                value: api_json_data.key.clone(),
-            },
-        ],
-    );
-    let response = get_response(url).await?;
-    let data: Value =
-        serde_json::from_str(&response).context("The given JSON input may be invalid.")?;
-
-    // Invalid API key error.
-    if let Some(401) = data["cod"].as_i64() {
-        return Err(anyhow!(get_file_read_error_message(
-            ErrorMessageType::InvalidApiKey,
-            None
-        )));
-    }
-
-    let mut cities: Vec<City> = vec![];
-
-    for city in data.as_array().unwrap() {
-        cities.push(City {
-            name: city["name"].as_str().unwrap().to_string(),
-            lat: city["lat"].as_f64().unwrap(),
-            lon: city["lon"].as_f64().unwrap(),
-            country: city["country"].as_str().unwrap().to_string(),
-        });
+    let response = get_response_with_api_key("geocoding", |api_key| {
+        replace_url_placeholders(
+            GEOLOCATION_API_URL,
+            &[
+                URLPlaceholder {
+                    placeholder: "{QUERY}".to_string(),
+                    value: query.to_string(),
+                },
+                URLPlaceholder {
+                    placeholder: "{API_KEY}".to_string(),
+                    value: api_key.to_string(),
+                },
+            ],
+        )
+    })
+    .await?;
+    let cities =
+        read_json_response::<Vec<City>>(response.status, &response.body, "GeocodingApiResponse")?;
+
+    if cities.is_empty() {
+        return Err(WeatherError::NotFound {
+            message: format!("No city matches '{}'.", query),
+            status: None,
+        }
+        .into());
     }
     display_cities(&cities);
 
-    match select_user_preferences(&cities) {
-        Ok((city_name, unit_name)) => {
-            println!("{} is now your city!", city_name);
-            println!("I'll use {} for you.", unit_name);
-        }
-        Err(e) => {
-            println!("ERROR: {}", e);
-        }
-    };
+    let (city_name, unit_name) = select_user_preferences(&cities)?;
+    println!("{} is now your city!", city_name);
+    println!("I'll use {} for you.", unit_name);
 
     Ok(())
 }
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::{
+    constants::CACHE_JSON_NAME,
+    read_json_file,
+    types::{
+        cache_types::{CacheEntry, ResponseCache},
+        user_settings::Units,
+    },
+    write_json_file,
+};
+
+/// Identifies a cached API response.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::{cache::CacheKey, types::user_settings::Units};
+/// let key = CacheKey {
+///     provider: "openweather",
+///     endpoint: "weather",
+///     lat: 43.6534817,
+///     lon: -79.3839347,
+///     units: &Units::Metric,
+/// };
+///
+/// assert_eq!(key.to_string(), "openweather/weather/43.6534817/-79.3839347/metric");
+/// ```
+pub struct CacheKey<'a> {
+    pub provider: &'a str,
+    pub endpoint: &'a str,
+    pub lat: f64,
+    pub lon: f64,
+    pub units: &'a Units,
+}
+
+impl fmt::Display for CacheKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}/{}",
+            self.provider, self.endpoint, self.lat, self.lon, self.units
+        )
+    }
+}
+
+impl CacheEntry {
+    /// Returns the age of the entry in seconds.
+    pub fn age(&self) -> i64 {
+        (Utc::now().timestamp() - self.fetched_at).max(0)
+    }
+
+    /// Checks whether the entry is younger than the given TTL.
+    pub fn is_fresh(&self, ttl_seconds: u64) -> bool {
+        self.age() < ttl_seconds as i64
+    }
+}
+
+/// Returns a cached response for the given key.
+pub fn read_cache_entry(key: &CacheKey) -> Result<Option<CacheEntry>> {
+    let cache = read_json_file::<ResponseCache>(CACHE_JSON_NAME)?;
+    Ok(cache.entries.get(&key.to_string()).cloned())
+}
+
+/// Stores a response body for the given key.
+pub fn write_cache_entry(key: &CacheKey, body: &str) -> Result<()> {
+    // A broken cache file shouldn't block new entries.
+    let mut cache = read_json_file::<ResponseCache>(CACHE_JSON_NAME).unwrap_or_default();
+    cache.entries.insert(
+        key.to_string(),
+        CacheEntry {
+            fetched_at: Utc::now().timestamp(),
+            body: body.to_string(),
+        },
+    );
+
+    write_json_file(CACHE_JSON_NAME, &cache)
+}
+
+/// Removes every cached response.
+pub fn clear_cache() -> Result<()> {
+    write_json_file(CACHE_JSON_NAME, &ResponseCache::default())
+}
+
+/// Formats an age in seconds as a human-readable string.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::cache::format_age;
+/// assert_eq!(format_age(42), "42 seconds");
+/// assert_eq!(format_age(150), "2 minutes");
+/// assert_eq!(format_age(7500), "2 hours 5 minutes");
+/// ```
+pub fn format_age(seconds: i64) -> String {
+    let plural = |value: i64, unit: &str| {
+        if value == 1 {
+            format!("{} {}", value, unit)
+        } else {
+            format!("{} {}s", value, unit)
+        }
+    };
+
+    match seconds {
+        s if s < 60 => plural(s, "second"),
+        s if s < 3600 => plural(s / 60, "minute"),
+        s if s < 86400 => match (s % 3600) / 60 {
+            0 => plural(s / 3600, "hour"),
+            minutes => format!("{} {}", plural(s / 3600, "hour"), plural(minutes, "minute")),
+        },
+        s => plural(s / 86400, "day"),
+    }
+}
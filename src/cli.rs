@@ -1,10 +1,27 @@
+use std::process::ExitCode;
+
+use anyhow::Result;
 use clap::Parser;
 
 use crate::{
-    api_usage::{print_weather_information, search_city},
-    get_executable_directory,
+    api_usage::{print_weather_information, search_city, CheckOptions, DetailLevel, OutputFormat},
+    clock::SystemClock,
+    constants::{API_JSON_NAME, USER_SETTING_JSON_NAME},
+    doctor::run_doctor,
+    error::{error_to_json, exit_code, WeatherError},
+    get_executable_directory, get_json_file_path,
+    icons::IconStyle,
+    moon::print_moon_information,
     program_info::ABOUT,
-    user_setup::setup_api,
+    redaction::redact,
+    sun::print_sun_information,
+    theme::ColorChoice,
+    usage::print_usage_report,
+    user_setup::{
+        add_named_api_key, clear_response_cache, decrypt_saved_api_key, list_api_keys,
+        print_config, remove_named_api_key, set_config_value, setup_api, setup_cache_ttl,
+        setup_usage_budget, ConfigKey,
+    },
 };
 
 #[derive(clap::Parser)]
@@ -12,12 +29,36 @@ use crate::{
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Render from cached data without any network request.
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Output format of `check`, `sun` and `moon`. Errors are also printed as JSON objects with `json`.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// When to color the output. `auto` colors a terminal unless `NO_COLOR` is set.
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
 }
 
 #[derive(clap::Subcommand)]
 enum Commands {
     /// Check weather information in your city
-    Check {},
+    Check {
+        /// Always request fresh data, bypassing the cache.
+        #[arg(long)]
+        no_cache: bool,
+
+        /// How much information to print.
+        #[arg(long, value_enum, default_value_t = DetailLevel::Normal)]
+        detail: DetailLevel,
+
+        /// Icon style. (default: the `icons` setting)
+        #[arg(long, value_enum)]
+        icons: Option<IconStyle>,
+    },
 
     /// Search and set your city
     SetLocation {
@@ -30,63 +71,283 @@ enum Commands {
     /// (https://openweathermap.org)
     SetupApi {
         /// API key from OpenWeather.
+        #[arg(
+            short,
+            long,
+            required_unless_present_any = ["encrypt", "decrypt", "remove", "list"]
+        )]
+        key: Option<String>,
+
+        /// Save the key without verifying it against the API.
+        #[arg(long)]
+        no_verify: bool,
+
+        /// Encrypt the key with a passphrase. (or `WEATHER_CLI_PASSPHRASE`)
+        /// Without `--key`, the saved key is encrypted.
+        #[arg(long, conflicts_with = "decrypt")]
+        encrypt: bool,
+
+        /// Decrypt the saved key and store it in plain text.
+        #[arg(long, conflicts_with = "key")]
+        decrypt: bool,
+
+        /// Add the key as a named key, tried when the other keys are
+        /// rejected (401) or rate limited (429).
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["encrypt", "decrypt"])]
+        add: Option<String>,
+
+        /// Remove a named key.
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["key", "encrypt", "decrypt", "add"])]
+        remove: Option<String>,
+
+        /// List the keys in the order they are tried.
+        #[arg(long, conflicts_with_all = ["key", "encrypt", "decrypt", "add", "remove"])]
+        list: bool,
+    },
+
+    /// Manage cached API responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+
+    /// View API usage or set a usage budget
+    Usage {
+        #[command(subcommand)]
+        command: Option<UsageCommands>,
+    },
+
+    /// Change program settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// View twilight, golden hour and sun position in your city, without the API
+    Sun {
+        /// Date to calculate. (ex. 2024-06-21, default: today in your city)
         #[arg(short, long)]
-        key: String,
+        date: Option<chrono::NaiveDate>,
     },
 
+    /// View the moon phase, moonrise and moonset in your city, without the API
+    Moon {
+        /// Date to calculate. (ex. 2024-06-21, default: today in your city)
+        #[arg(short, long)]
+        date: Option<chrono::NaiveDate>,
+    },
+
+    /// Diagnose the setup (files, API key, network, cache, clock)
+    Doctor {},
+
     /// View information about the program
     About {},
 }
 
-pub async fn init() {
-    let cli = Cli::parse();
+#[derive(clap::Subcommand)]
+enum ConfigCommands {
+    /// Set a setting value
+    Set {
+        /// Setting name.
+        key: ConfigKey,
+
+        /// New value.
+        value: String,
+    },
+
+    /// Restore the default value of a setting
+    Unset {
+        /// Setting name.
+        key: ConfigKey,
+    },
+
+    /// View the current settings and the API key source
+    Show {},
+}
+
+#[derive(clap::Subcommand)]
+enum UsageCommands {
+    /// View or set the API usage budget
+    Budget {
+        /// Maximum number of calls per day (UTC). `0` removes the limit.
+        #[arg(short, long)]
+        daily: Option<u32>,
+
+        /// Maximum number of calls per minute. `0` removes the limit.
+        #[arg(short, long)]
+        minute: Option<u32>,
+    },
+}
 
+#[derive(clap::Subcommand)]
+enum CacheCommands {
+    /// Remove every cached response
+    Clear {},
+
+    /// View or set how long cached responses stay fresh
+    Ttl {
+        /// Time to live in seconds.
+        #[arg(short, long)]
+        seconds: Option<u64>,
+    },
+}
+
+/// Prints information about the program.
+fn print_about() {
+    use crate::program_info::{
+        CRATES_IO_URL, PROGRAM_AUTHORS, PROGRAM_DESCRIPTION, PROGRAM_NAME, REPOSITORY_URL,
+    };
+
+    let splitted_author_list: Vec<&str> = PROGRAM_AUTHORS.split(',').collect();
+
+    let mut authors = String::new();
+    for (index, one) in splitted_author_list.into_iter().enumerate() {
+        if index == 0 {
+            authors += one.trim();
+        } else {
+            authors = authors + ", " + one.trim();
+        }
+    }
+
+    println!("# {}", PROGRAM_NAME);
+    println!("{}\n", PROGRAM_DESCRIPTION);
+    println!("Developed by: {}", authors);
+    println!("- crates.io: {}", CRATES_IO_URL);
+    println!("- Github: {}", REPOSITORY_URL);
+}
+
+/// Runs the given command.
+async fn run(cli: &Cli) -> Result<()> {
     match &cli.command {
-        Some(Commands::Check {}) => {
-            match print_weather_information().await {
-                Ok(()) => {}
-                Err(e) => {
-                    println!("ERROR: {}", e);
-                }
+        Some(Commands::Check {
+            no_cache,
+            detail,
+            icons,
+        }) => {
+            let options = CheckOptions {
+                no_cache: *no_cache,
+                offline: cli.offline,
+                output: cli.output,
+                detail: *detail,
+                icons: *icons,
+                color: cli.color,
             };
+
+            print_weather_information(&options).await
         }
         Some(Commands::SetLocation { query }) => {
-            search_city(query).await.unwrap_or_else(|e| {
-                println!("ERROR: {}", e);
-            });
-        }
-        Some(Commands::SetupApi { key }) => {
-            setup_api(key.to_string()).unwrap_or_else(|e| {
-                println!("ERROR: {}", e);
-            });
-        }
-        Some(Commands::About {}) => {
-            use crate::program_info::{
-                CRATES_IO_URL, PROGRAM_AUTHORS, PROGRAM_DESCRIPTION, PROGRAM_NAME, REPOSITORY_URL,
-            };
-
-            let splitted_author_list: Vec<&str> = PROGRAM_AUTHORS.split(',').collect();
-
-            let mut authors = String::new();
-            for (index, one) in splitted_author_list.into_iter().enumerate() {
-                if index == 0 {
-                    authors += one.trim();
-                } else {
-                    authors = authors + ", " + one.trim();
+            if cli.offline {
+                return Err(WeatherError::Network {
+                    message: "Searching cities isn't available in offline mode.".to_string(),
+                    status: None,
                 }
+                .into());
             }
 
-            println!("# {}", PROGRAM_NAME);
-            println!("{}\n", PROGRAM_DESCRIPTION);
-            println!("Developed by: {}", authors);
-            println!("- crates.io: {}", CRATES_IO_URL);
-            println!("- Github: {}", REPOSITORY_URL);
+            search_city(query).await
+        }
+        Some(Commands::SetupApi {
+            key,
+            no_verify,
+            encrypt,
+            decrypt,
+            add,
+            remove,
+            list,
+        }) => {
+            let verify = !no_verify && !cli.offline;
+            if *list {
+                list_api_keys()
+            } else if let Some(name) = remove {
+                remove_named_api_key(name)
+            } else if let (Some(name), Some(key)) = (add, key) {
+                add_named_api_key(name, key.clone(), verify).await
+            } else if *decrypt {
+                decrypt_saved_api_key()
+            } else {
+                setup_api(key.clone(), verify, *encrypt).await
+            }
+        }
+        Some(Commands::Cache { command }) => match command {
+            CacheCommands::Clear {} => clear_response_cache(),
+            CacheCommands::Ttl { seconds } => setup_cache_ttl(*seconds),
+        },
+        Some(Commands::Usage { command }) => match command {
+            None => print_usage_report(),
+            Some(UsageCommands::Budget { daily, minute }) => setup_usage_budget(*daily, *minute),
+        },
+        Some(Commands::Config { command }) => match command {
+            ConfigCommands::Set { key, value } => set_config_value(*key, Some(value)),
+            ConfigCommands::Unset { key } => set_config_value(*key, None),
+            ConfigCommands::Show {} => print_config(),
+        },
+        Some(Commands::Sun { date }) => print_sun_information(*date, cli.output, &SystemClock),
+        Some(Commands::Moon { date }) => print_moon_information(*date, cli.output, &SystemClock),
+        Some(Commands::Doctor {}) => run_doctor().await,
+        Some(Commands::About {}) => {
+            print_about();
+            Ok(())
         }
         None => {
             println!("Please use \"weather-cli help\" command for help.");
 
-            let executable_directory = get_executable_directory().unwrap();
+            let executable_directory = get_executable_directory()?;
             println!("- Program Executable Directory: {}", executable_directory);
+            println!(
+                "- Settings: {}",
+                get_json_file_path(USER_SETTING_JSON_NAME)?
+            );
+            println!("- API Key: {}", get_json_file_path(API_JSON_NAME)?);
+            println!("Please use \"weather-cli doctor\" command to diagnose problems.");
+            Ok(())
+        }
+    }
+}
+
+/// Returns the `ERROR` label of stderr, colored with the `warning` color of the theme.
+fn error_label(cli: &Cli) -> String {
+    use std::io::IsTerminal;
+
+    use crate::{
+        constants::USER_SETTING_JSON_NAME,
+        read_json_file,
+        theme::{Painter, Theme},
+        types::user_settings::UserSetting,
+    };
+
+    if !cli.color.is_enabled(std::io::stderr().is_terminal()) {
+        return "ERROR".to_string();
+    }
+
+    // The settings may be the cause of the error, so a broken theme falls back to the default.
+    let theme = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)
+        .and_then(|setting| Theme::load(setting.theme.unwrap_or_default()))
+        .unwrap_or_default();
+    Painter {
+        theme,
+        enabled: true,
+    }
+    .warning("ERROR")
+}
+
+/// Runs the program and returns the exit code.
+/// See `error::WeatherError` for the exit codes.
+///
+/// Errors are printed to stderr, or to stdout as JSON objects
+/// with `--output json` so wrapper tools can parse them.
+pub async fn init() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match cli.output {
+                OutputFormat::Text => {
+                    eprintln!("{}: {}", error_label(&cli), redact(&e.to_string()))
+                }
+                OutputFormat::Json => println!("{}", error_to_json(&e)),
+            }
+            ExitCode::from(exit_code(&e))
         }
     }
 }
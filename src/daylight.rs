@@ -0,0 +1,108 @@
+// Upcoming sunrise and sunset from the times in the API response.
+// The API only sends today's sunrise and sunset, so later events are computed
+// with the NOAA equations in `sun`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc};
+
+use crate::{clock::Clock, time_display::TimeFormatter};
+
+pub enum EventInfo<T: TimeZone> {
+    Sunrise(DateTime<T>),
+    Sunset(DateTime<T>),
+}
+
+impl<T: TimeZone> EventInfo<T>
+where
+    T::Offset: std::fmt::Display,
+{
+    /// Formats the event with the time settings. See `time_display::TimeFormatter`.
+    pub fn describe(&self, formatter: &TimeFormatter, now: Option<DateTime<Utc>>) -> String {
+        match self {
+            EventInfo::Sunrise(sunrise_time) => formatter.event("Sunrise", sunrise_time, now),
+            EventInfo::Sunset(sunset_time) => formatter.event("Sunset", sunset_time, now),
+        }
+    }
+}
+
+/// Sunrise and sunset of a city.
+pub enum Daylight {
+    /// The next event, and the one after it.
+    /// The second one is `None` when polar day or night begins after the first one.
+    Events(EventInfo<FixedOffset>, Option<EventInfo<FixedOffset>>),
+    /// The sun stays above the horizon all day.
+    PolarDay,
+    /// The sun stays below the horizon all day.
+    PolarNight,
+}
+
+/// Returns the first sunrise (`rising`) or sunset after `now`, within the next two days.
+fn next_crossing(
+    now: DateTime<Utc>,
+    latitude: f64,
+    longitude: f64,
+    rising: bool,
+) -> Option<DateTime<Utc>> {
+    use crate::sun::{crossing_time, SUNRISE_ALTITUDE};
+
+    (-1..=2)
+        .filter_map(|days| {
+            let date = now.date_naive() + Duration::days(days);
+            crossing_time(date, latitude, longitude, SUNRISE_ALTITUDE, rising)
+        })
+        .filter(|time| *time > now)
+        .min()
+}
+
+/// Returns the upcoming sunrise and sunset.
+///
+/// The times from the API are used while they are upcoming.
+/// Later events are computed, as the API sends `0` or identical timestamps
+/// when the sun doesn't rise or set, and data may come from the cache.
+/// Without any event in the next two days, it's polar day or night.
+pub fn upcoming_sun_events(
+    sunrise_timestamp: i64,
+    sunset_timestamp: i64,
+    timezone: i32,
+    latitude: f64,
+    longitude: f64,
+    clock: &impl Clock,
+) -> Result<Daylight> {
+    use crate::sun::{solar_noon, sun_position, SUNRISE_ALTITUDE};
+
+    let timezone = FixedOffset::east_opt(timezone).context("Failed to read timezone value.")?;
+    let now = clock.now();
+
+    let is_event =
+        sunrise_timestamp != 0 && sunset_timestamp != 0 && sunrise_timestamp != sunset_timestamp;
+    let upcoming = |timestamp: i64, rising: bool| {
+        DateTime::<Utc>::from_timestamp(timestamp, 0)
+            .filter(|time| is_event && *time > now)
+            .or_else(|| next_crossing(now, latitude, longitude, rising))
+            .map(|time| time.with_timezone(&timezone))
+    };
+
+    // The first element is the next upcoming event.
+    let daylight = match (
+        upcoming(sunrise_timestamp, true),
+        upcoming(sunset_timestamp, false),
+    ) {
+        (Some(sunrise), Some(sunset)) if sunrise < sunset => {
+            Daylight::Events(EventInfo::Sunrise(sunrise), Some(EventInfo::Sunset(sunset)))
+        }
+        (Some(sunrise), Some(sunset)) => {
+            Daylight::Events(EventInfo::Sunset(sunset), Some(EventInfo::Sunrise(sunrise)))
+        }
+        (Some(sunrise), None) => Daylight::Events(EventInfo::Sunrise(sunrise), None),
+        (None, Some(sunset)) => Daylight::Events(EventInfo::Sunset(sunset), None),
+        (None, None) => {
+            let noon = solar_noon(now.date_naive(), longitude);
+            match sun_position(noon, latitude, longitude).elevation > SUNRISE_ALTITUDE {
+                true => Daylight::PolarDay,
+                false => Daylight::PolarNight,
+            }
+        }
+    };
+
+    Ok(daylight)
+}
@@ -0,0 +1,416 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    constants::{
+        API_JSON_NAME, CACHE_JSON_NAME, GEOLOCATION_API_URL, USAGE_JSON_NAME,
+        USER_SETTING_JSON_NAME, WEATHER_API_URL,
+    },
+    redaction::redact,
+    types::user_settings::UserSetting,
+};
+
+/// Timeout of each network check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Clock skew that is still tolerated, in seconds.
+const MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Result of a single diagnostic check.
+#[derive(Clone, Copy, PartialEq)]
+enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// Collects and prints diagnostic check results.
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn section(&self, title: &str) {
+        println!("\n* {}", title);
+    }
+
+    fn add(&mut self, status: CheckStatus, message: impl AsRef<str>) {
+        let label = match status {
+            CheckStatus::Ok => "[ OK ]",
+            CheckStatus::Warn => "[WARN]",
+            CheckStatus::Fail => "[FAIL]",
+        };
+        if status == CheckStatus::Fail {
+            self.failures += 1;
+        }
+
+        println!("{} {}", label, redact(message.as_ref()));
+    }
+}
+
+/// Returns `(scheme, host, port)` of an endpoint URL.
+fn endpoint_address(url: &str) -> Option<(String, String, u16)> {
+    let url = reqwest::Url::parse(url).ok()?;
+
+    Some((
+        url.scheme().to_string(),
+        url.host_str()?.to_string(),
+        url.port_or_known_default()?,
+    ))
+}
+
+/// Checks the location, permissions and contents of the JSON files.
+fn check_config_files(report: &mut Report) {
+    use std::fs;
+
+    use crate::{get_executable_directory, get_json_file_path};
+
+    report.section("Configuration files");
+
+    match get_executable_directory() {
+        Ok(dir) => {
+            // The probe file is removed at once, so no file is left behind.
+            let probe_path = format!("{}/.weather-cli-doctor-{}", dir, std::process::id());
+            let writable = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&probe_path)
+                .map(|_| fs::remove_file(&probe_path))
+                .is_ok();
+            if writable {
+                report.add(CheckStatus::Ok, format!("Directory: {}", dir));
+            } else {
+                report.add(
+                    CheckStatus::Fail,
+                    format!("Directory isn't writable: {}", dir),
+                );
+            }
+        }
+        Err(e) => report.add(CheckStatus::Fail, format!("Directory: {}", e)),
+    }
+
+    for json_name in [
+        API_JSON_NAME,
+        USER_SETTING_JSON_NAME,
+        CACHE_JSON_NAME,
+        USAGE_JSON_NAME,
+    ] {
+        let Ok(path) = get_json_file_path(json_name) else {
+            continue;
+        };
+
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                let status = match json_name {
+                    API_JSON_NAME | USER_SETTING_JSON_NAME => CheckStatus::Warn,
+                    _ => CheckStatus::Ok,
+                };
+                report.add(status, format!("{}: not created yet", path));
+                continue;
+            }
+        };
+
+        let is_valid_json = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+            .is_some();
+        if !is_valid_json {
+            report.add(CheckStatus::Fail, format!("{}: invalid JSON", path));
+            continue;
+        }
+
+        if fs::OpenOptions::new().append(true).open(&path).is_err() {
+            report.add(CheckStatus::Fail, format!("{}: not writable", path));
+            continue;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = metadata.permissions().mode() & 0o777;
+            if json_name == API_JSON_NAME && mode & 0o077 != 0 {
+                report.add(
+                    CheckStatus::Warn,
+                    format!(
+                        "{}: readable by other users ({:o}). Consider `chmod 600`.",
+                        path, mode
+                    ),
+                );
+            } else {
+                report.add(CheckStatus::Ok, format!("{} ({:o})", path, mode));
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            report.add(CheckStatus::Ok, path);
+        }
+    }
+}
+
+/// Checks whether the API key is present and well-formed.
+fn check_api_key(report: &mut Report) -> Option<String> {
+    use crate::{
+        api_key::{environment_api_key, resolve_api_keys},
+        read_existing_json_file,
+        types::user_settings::ApiSetting,
+        user_setup::is_api_key_well_formed,
+    };
+
+    report.section("API key");
+
+    let api_key = read_existing_json_file::<ApiSetting>(API_JSON_NAME)
+        .map(Option::unwrap_or_default)
+        .and_then(|api_setting| resolve_api_keys(&api_setting, environment_api_key().as_deref()))
+        .map(|mut keys| keys.remove(0));
+    match api_key {
+        Ok(api_key) if is_api_key_well_formed(&api_key.key) => {
+            report.add(
+                CheckStatus::Ok,
+                format!(
+                    "API key is present and well-formed. (from {})",
+                    api_key.source
+                ),
+            );
+            Some(api_key.key)
+        }
+        Ok(_) => {
+            report.add(
+                CheckStatus::Fail,
+                "API key isn't 32 alphanumeric characters. Please check your API key setting.",
+            );
+            None
+        }
+        Err(e) => {
+            report.add(CheckStatus::Fail, e.to_string());
+            None
+        }
+    }
+}
+
+/// Checks DNS, TCP and TLS reachability of every endpoint.
+/// Returns the server time from the first successful HTTP response.
+async fn check_endpoints(report: &mut Report, setting: &UserSetting) -> Option<DateTime<Utc>> {
+    use tokio::{net::TcpStream, time::timeout};
+
+    use crate::http::{build_client, uses_proxy};
+
+    report.section("Network");
+
+    let mut addresses: Vec<(String, String, u16)> = vec![];
+    for url in [WEATHER_API_URL, GEOLOCATION_API_URL] {
+        if let Some(address) = endpoint_address(url) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+
+    let mut server_time = None;
+    for (scheme, host, port) in addresses {
+        let name = format!("{}://{}:{}", scheme, host, port);
+
+        // Behind a proxy, only the proxy has to be reachable from here.
+        if uses_proxy(&setting.network) {
+            report.add(
+                CheckStatus::Ok,
+                format!("{}: connecting through a proxy", name),
+            );
+        } else {
+            let socket_addresses: Vec<_> = match timeout(
+                CHECK_TIMEOUT,
+                tokio::net::lookup_host((host.as_str(), port)),
+            )
+            .await
+            {
+                Ok(Ok(addresses)) => addresses.collect(),
+                _ => {
+                    report.add(CheckStatus::Fail, format!("{}: DNS lookup failed", name));
+                    continue;
+                }
+            };
+            report.add(
+                CheckStatus::Ok,
+                format!(
+                    "{}: DNS resolved ({} addresses)",
+                    name,
+                    socket_addresses.len()
+                ),
+            );
+
+            match timeout(CHECK_TIMEOUT, TcpStream::connect(&socket_addresses[..])).await {
+                Ok(Ok(_)) => report.add(CheckStatus::Ok, format!("{}: TCP connected", name)),
+                _ => {
+                    report.add(
+                        CheckStatus::Fail,
+                        format!("{}: TCP connection failed", name),
+                    );
+                    continue;
+                }
+            }
+        }
+
+        let response = match build_client(&setting.network) {
+            Ok(client) => {
+                client
+                    .head(format!("{}://{}:{}/", scheme, host, port))
+                    .timeout(CHECK_TIMEOUT)
+                    .send()
+                    .await
+            }
+            Err(e) => {
+                report.add(CheckStatus::Fail, format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        let protocol = if scheme == "https" { "TLS" } else { "HTTP" };
+        match response {
+            Ok(response) => {
+                report.add(
+                    CheckStatus::Ok,
+                    format!("{}: {} handshake succeeded", name, protocol),
+                );
+                if scheme != "https" {
+                    report.add(
+                        CheckStatus::Warn,
+                        format!("{}: requests aren't encrypted", name),
+                    );
+                }
+
+                server_time = server_time.or_else(|| {
+                    response
+                        .headers()
+                        .get(reqwest::header::DATE)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+                        .map(|time| time.with_timezone(&Utc))
+                });
+            }
+            Err(e) => report.add(
+                CheckStatus::Fail,
+                format!("{}: {} failed ({})", name, protocol, e),
+            ),
+        }
+    }
+
+    server_time
+}
+
+/// Checks whether the API key authenticates with a single request.
+/// The request isn't recorded in the usage ledger, so no file is written.
+async fn check_authentication(report: &mut Report, api_key: &str, setting: &UserSetting) {
+    use crate::{error::WeatherError, http::RequestOptions, user_setup::verify_api_key};
+
+    report.section("Authentication");
+
+    let options = RequestOptions {
+        network: &setting.network,
+        retry_rate_limited: false,
+        track_usage: false,
+    };
+    // Rate limits and server errors don't tell whether the key is valid.
+    match verify_api_key(api_key, &options).await {
+        Ok(()) => report.add(CheckStatus::Ok, "API key authenticates."),
+        Err(e) => match e.downcast_ref::<WeatherError>() {
+            Some(error) if error.is_retryable() => report.add(
+                CheckStatus::Warn,
+                format!("Couldn't check the API key: {}", e),
+            ),
+            Some(WeatherError::InvalidApiKey) => report.add(
+                CheckStatus::Fail,
+                format!(
+                    "{} A key created within the last few hours may not be activated yet.",
+                    e
+                ),
+            ),
+            _ => report.add(CheckStatus::Fail, e.to_string()),
+        },
+    }
+}
+
+/// Checks whether the response cache can be read.
+fn check_cache(report: &mut Report) {
+    use crate::{cache::format_age, read_existing_json_file, types::cache_types::ResponseCache};
+
+    report.section("Cache");
+
+    match read_existing_json_file::<ResponseCache>(CACHE_JSON_NAME) {
+        Ok(None) => report.add(CheckStatus::Ok, "Cache is empty."),
+        Ok(Some(cache)) => {
+            let newest = cache.entries.values().map(|entry| entry.age()).min();
+            match newest {
+                Some(age) => report.add(
+                    CheckStatus::Ok,
+                    format!(
+                        "{} entries, the newest one is {} old.",
+                        cache.entries.len(),
+                        format_age(age)
+                    ),
+                ),
+                None => report.add(CheckStatus::Ok, "Cache is empty."),
+            }
+        }
+        Err(e) => report.add(
+            CheckStatus::Fail,
+            format!("{} Run 'cache clear' to reset it.", e),
+        ),
+    }
+}
+
+/// Compares the local clock with the server time.
+fn check_clock(report: &mut Report, server_time: Option<DateTime<Utc>>) {
+    report.section("Clock");
+
+    let Some(server_time) = server_time else {
+        report.add(CheckStatus::Warn, "No server time to compare with.");
+        return;
+    };
+
+    let skew = (Utc::now() - server_time).num_seconds();
+    if skew.abs() > MAX_CLOCK_SKEW_SECONDS {
+        report.add(
+            CheckStatus::Warn,
+            format!(
+                "Local clock is {} seconds off. Sunrise and sunset times may be wrong.",
+                skew
+            ),
+        );
+    } else {
+        report.add(
+            CheckStatus::Ok,
+            format!("Local clock is {} seconds off.", skew),
+        );
+    }
+}
+
+/// Diagnoses the setup and prints the results.
+/// Returns an error when any check fails.
+pub async fn run_doctor() -> Result<()> {
+    use crate::read_existing_json_file;
+
+    // Missing files are reported by `check_config_files`, so they aren't created here.
+    let mut report = Report::default();
+    let setting = read_existing_json_file::<UserSetting>(USER_SETTING_JSON_NAME)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    check_config_files(&mut report);
+    let api_key = check_api_key(&mut report);
+    let server_time = check_endpoints(&mut report, &setting).await;
+    if let Some(api_key) = api_key {
+        check_authentication(&mut report, &api_key, &setting).await;
+    }
+    check_cache(&mut report);
+    check_clock(&mut report, server_time);
+
+    match report.failures {
+        0 => Ok(()),
+        failures => Err(anyhow!("{} check(s) failed.", failures)),
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use crate::redaction::redact;
+
+/// Errors with a distinct process exit code.
+///
+/// | exit code | error                                        |
+/// | --------- | -------------------------------------------- |
+/// | 0         | Success                                      |
+/// | 1         | Other errors                                 |
+/// | 2         | Invalid command-line arguments               |
+/// | 3         | `ConfigMissing`                              |
+/// | 4         | `InvalidApiKey`                              |
+/// | 5         | `Network`                                    |
+/// | 6         | `RateLimited`                                |
+/// | 7         | `NotFound`                                   |
+/// | 8         | `Parse`                                      |
+/// | 9         | `Api`                                        |
+#[derive(Debug)]
+pub enum WeatherError {
+    /// A required setting is missing. (ex. API key, city)
+    ConfigMissing(String),
+    /// The API key was rejected.
+    InvalidApiKey,
+    /// The request couldn't be completed.
+    Network {
+        message: String,
+        status: Option<u16>,
+    },
+    /// The API rate limit or the local usage budget is exceeded.
+    RateLimited {
+        message: String,
+        status: Option<u16>,
+    },
+    /// The requested resource doesn't exist.
+    NotFound {
+        message: String,
+        status: Option<u16>,
+    },
+    /// A response or a file couldn't be parsed.
+    Parse(String),
+    /// The API rejected the request for another reason.
+    Api { message: String, status: u16 },
+}
+
+impl WeatherError {
+    /// Returns the process exit code of the error.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::error::WeatherError;
+    /// assert_eq!(WeatherError::InvalidApiKey.exit_code(), 4);
+    /// ```
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            WeatherError::ConfigMissing(_) => 3,
+            WeatherError::InvalidApiKey => 4,
+            WeatherError::Network { .. } => 5,
+            WeatherError::RateLimited { .. } => 6,
+            WeatherError::NotFound { .. } => 7,
+            WeatherError::Parse(_) => 8,
+            WeatherError::Api { .. } => 9,
+        }
+    }
+
+    /// Returns the error kind used in JSON output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WeatherError::ConfigMissing(_) => "config_missing",
+            WeatherError::InvalidApiKey => "invalid_api_key",
+            WeatherError::Network { .. } => "network",
+            WeatherError::RateLimited { .. } => "rate_limited",
+            WeatherError::NotFound { .. } => "not_found",
+            WeatherError::Parse(_) => "parse",
+            WeatherError::Api { .. } => "api",
+        }
+    }
+
+    /// Returns the HTTP status code of the response that caused the error.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            WeatherError::InvalidApiKey => Some(401),
+            WeatherError::Network { status, .. }
+            | WeatherError::RateLimited { status, .. }
+            | WeatherError::NotFound { status, .. } => *status,
+            WeatherError::Api { status, .. } => Some(*status),
+            WeatherError::ConfigMissing(_) | WeatherError::Parse(_) => None,
+        }
+    }
+
+    /// Checks whether trying again later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            WeatherError::Network { .. } | WeatherError::RateLimited { .. }
+        )
+    }
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeatherError::InvalidApiKey => f.write_str("API Key is invalid. Please try again."),
+            WeatherError::ConfigMissing(message) | WeatherError::Parse(message) => {
+                f.write_str(message)
+            }
+            WeatherError::Network { message, .. }
+            | WeatherError::RateLimited { message, .. }
+            | WeatherError::NotFound { message, .. }
+            | WeatherError::Api { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for WeatherError {}
+
+/// Returns the first `WeatherError` in the error chain.
+fn find_weather_error(error: &anyhow::Error) -> Option<&WeatherError> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<WeatherError>())
+}
+
+/// Returns the process exit code of an error.
+/// Errors without a `WeatherError` in their chain use `1`.
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    find_weather_error(error).map_or(1, WeatherError::exit_code)
+}
+
+/// Returns a JSON object describing an error.
+/// Errors without a `WeatherError` in their chain use the `other` kind.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::error::{error_to_json, WeatherError};
+/// let error = anyhow::Error::from(WeatherError::InvalidApiKey);
+///
+/// assert_eq!(
+///     error_to_json(&error).to_string(),
+///     r#"{"error":{"http_status":401,"kind":"invalid_api_key","message":"API Key is invalid. Please try again.","retryable":false}}"#
+/// );
+/// ```
+pub fn error_to_json(error: &anyhow::Error) -> serde_json::Value {
+    let weather_error = find_weather_error(error);
+
+    serde_json::json!({
+        "error": {
+            "kind": weather_error.map_or("other", WeatherError::kind),
+            "message": redact(&error.to_string()),
+            "http_status": weather_error.and_then(WeatherError::http_status),
+            "retryable": weather_error.is_some_and(WeatherError::is_retryable),
+        }
+    })
+}
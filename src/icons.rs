@@ -0,0 +1,218 @@
+// Weather icons and ASCII art.
+// Icons are chosen by the condition code (`Weather.id`),
+// and the day/night suffix of `Weather.icon`. (ex. `01d`, `01n`)
+
+use crate::types::response_types::{ConditionCategory, Weather};
+
+/// How weather conditions are drawn.
+#[derive(
+    serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconStyle {
+    /// No icons.
+    #[default]
+    None,
+    /// Nerd Font glyphs. (https://www.nerdfonts.com)
+    NerdFont,
+    /// Unicode emoji.
+    Emoji,
+    /// Multi-line ASCII art.
+    Ascii,
+}
+
+impl std::fmt::Display for IconStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            IconStyle::None => "none",
+            IconStyle::NerdFont => "nerd-font",
+            IconStyle::Emoji => "emoji",
+            IconStyle::Ascii => "ascii",
+        })
+    }
+}
+
+/// Kinds of icons. Several condition codes share the same icon.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IconKind {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Drizzle,
+    Rain,
+    Thunderstorm,
+    Snow,
+    Fog,
+    Tornado,
+    Unknown,
+}
+
+impl IconKind {
+    /// Returns the icon kind of a weather condition.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::icons::IconKind;
+    /// assert_eq!(IconKind::from_id(Some(800)), IconKind::Clear);
+    /// assert_eq!(IconKind::from_id(Some(802)), IconKind::PartlyCloudy);
+    /// assert_eq!(IconKind::from_id(Some(781)), IconKind::Tornado);
+    /// assert_eq!(IconKind::from_id(None), IconKind::Unknown);
+    /// ```
+    pub fn from_id(id: Option<u32>) -> IconKind {
+        let Some(id) = id else {
+            return IconKind::Unknown;
+        };
+
+        match (ConditionCategory::from_id(id), id) {
+            (_, 781) => IconKind::Tornado,
+            (_, 801 | 802) => IconKind::PartlyCloudy,
+            (Some(ConditionCategory::Clear), _) => IconKind::Clear,
+            (Some(ConditionCategory::Clouds), _) => IconKind::Cloudy,
+            (Some(ConditionCategory::Drizzle), _) => IconKind::Drizzle,
+            (Some(ConditionCategory::Rain), _) => IconKind::Rain,
+            (Some(ConditionCategory::Thunderstorm), _) => IconKind::Thunderstorm,
+            (Some(ConditionCategory::Snow), _) => IconKind::Snow,
+            (Some(ConditionCategory::Atmosphere), _) => IconKind::Fog,
+            (None, _) => IconKind::Unknown,
+        }
+    }
+}
+
+/// Whether a condition is observed at night, from the `n` suffix of `Weather.icon`.
+pub fn is_night(weather: &Weather) -> bool {
+    weather
+        .icon
+        .as_deref()
+        .is_some_and(|icon| icon.ends_with('n'))
+}
+
+/// Returns a single-character icon of a weather condition.
+/// `None` for `IconStyle::None` and `IconStyle::Ascii`.
+pub fn condition_icon(weather: &Weather, style: IconStyle) -> Option<&'static str> {
+    let kind = IconKind::from_id(weather.id);
+    let night = is_night(weather);
+
+    let icon = match style {
+        IconStyle::None | IconStyle::Ascii => return None,
+        IconStyle::NerdFont => match (kind, night) {
+            (IconKind::Clear, false) => "\u{e30d}",
+            (IconKind::Clear, true) => "\u{e32b}",
+            (IconKind::PartlyCloudy, false) => "\u{e302}",
+            (IconKind::PartlyCloudy, true) => "\u{e37e}",
+            (IconKind::Cloudy, _) => "\u{e312}",
+            (IconKind::Drizzle, _) => "\u{e31b}",
+            (IconKind::Rain, _) => "\u{e318}",
+            (IconKind::Thunderstorm, _) => "\u{e31d}",
+            (IconKind::Snow, _) => "\u{e31a}",
+            (IconKind::Fog, _) => "\u{e313}",
+            (IconKind::Tornado, _) => "\u{e351}",
+            (IconKind::Unknown, _) => "\u{e374}",
+        },
+        IconStyle::Emoji => match (kind, night) {
+            (IconKind::Clear, false) => "☀️",
+            (IconKind::Clear, true) => "🌙",
+            (IconKind::PartlyCloudy, false) => "⛅",
+            (IconKind::PartlyCloudy, true) => "☁️",
+            (IconKind::Cloudy, _) => "☁️",
+            (IconKind::Drizzle, _) => "🌦️",
+            (IconKind::Rain, _) => "🌧️",
+            (IconKind::Thunderstorm, _) => "⛈️",
+            (IconKind::Snow, _) => "🌨️",
+            (IconKind::Fog, _) => "🌫️",
+            (IconKind::Tornado, _) => "🌪️",
+            (IconKind::Unknown, _) => "❔",
+        },
+    };
+
+    Some(icon)
+}
+
+/// Returns 5 lines of ASCII art of a weather condition. Every line is 13 characters wide.
+pub fn ascii_art(weather: &Weather) -> [&'static str; 5] {
+    match (IconKind::from_id(weather.id), is_night(weather)) {
+        (IconKind::Clear, false) => [
+            r"    \   /    ",
+            r"     .-.     ",
+            r"  - (   ) -  ",
+            r"     `-'     ",
+            r"    /   \    ",
+        ],
+        (IconKind::Clear, true) => [
+            r"     _..     ",
+            r"   .' .'     ",
+            r"  (  (       ",
+            r"   '. '.     ",
+            r"     `''     ",
+        ],
+        (IconKind::PartlyCloudy, false) => [
+            r"   \  /      ",
+            r" _ /''.-.    ",
+            r"   \_(   ).  ",
+            r"   /(___(__) ",
+            r"             ",
+        ],
+        (IconKind::PartlyCloudy, true) => [
+            r"    _..      ",
+            r"  .' .-.     ",
+            r" (  (   ).   ",
+            r"  '(___(__)  ",
+            r"             ",
+        ],
+        (IconKind::Cloudy, _) => [
+            r"             ",
+            r"     .--.    ",
+            r"  .-(    ).  ",
+            r" (___.__)__) ",
+            r"             ",
+        ],
+        (IconKind::Drizzle, _) => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"    '   '    ",
+            r"      '   '  ",
+        ],
+        (IconKind::Rain, _) => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"  ' ' ' ' '  ",
+            r" ' ' ' ' '   ",
+        ],
+        (IconKind::Thunderstorm, _) => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"   ' /_' /_  ",
+            r"    /   /    ",
+        ],
+        (IconKind::Snow, _) => [
+            r"     .-.     ",
+            r"    (   ).   ",
+            r"   (___(__)  ",
+            r"   *  *  *   ",
+            r"  *  *  *    ",
+        ],
+        (IconKind::Fog, _) => [
+            r"             ",
+            r" _ - _ - _ - ",
+            r"  _ - _ - _  ",
+            r" _ - _ - _ - ",
+            r"             ",
+        ],
+        (IconKind::Tornado, _) => [
+            r" '~~~~~~~~~' ",
+            r"  '~~~~~~~'  ",
+            r"   '~~~~~'   ",
+            r"     ~~~     ",
+            r"      ~      ",
+        ],
+        (IconKind::Unknown, _) => [
+            r"    .-.      ",
+            r"     __)     ",
+            r"    (        ",
+            r"     `-'     ",
+            r"      *      ",
+        ],
+    }
+}
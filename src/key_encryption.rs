@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::types::user_settings::EncryptedApiKey;
+
+/// Environment variable containing the passphrase of an encrypted API key.
+pub const PASSPHRASE_ENV_NAME: &str = "WEATHER_CLI_PASSPHRASE";
+
+/// Derives a 256-bit encryption key from a passphrase with Argon2id.
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    use argon2::Argon2;
+
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive the encryption key: {}", e))?;
+
+    Ok(key)
+}
+
+/// Encrypts an API key with a passphrase. (Argon2id + XChaCha20-Poly1305)
+pub fn encrypt_api_key(api_key: &str, passphrase: &str) -> Result<EncryptedApiKey> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, api_key.as_bytes())
+        .map_err(|_| anyhow!("Failed to encrypt the API key."))?;
+
+    Ok(EncryptedApiKey {
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+/// Decrypts an API key with a passphrase.
+pub fn decrypt_api_key(encrypted: &EncryptedApiKey, passphrase: &str) -> Result<String> {
+    let invalid_data_error = "The encrypted API key is corrupted.";
+
+    let salt = BASE64.decode(&encrypted.salt).context(invalid_data_error)?;
+    let nonce = BASE64
+        .decode(&encrypted.nonce)
+        .context(invalid_data_error)?;
+    let ciphertext = BASE64
+        .decode(&encrypted.ciphertext)
+        .context(invalid_data_error)?;
+    if nonce.len() != 24 {
+        return Err(anyhow!(invalid_data_error));
+    }
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?.into());
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("Failed to decrypt the API key. The passphrase may be wrong."))?;
+
+    String::from_utf8(plaintext).context(invalid_data_error)
+}
+
+/// Checks that a passphrase isn't empty.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::key_encryption::validate_passphrase;
+/// assert!(validate_passphrase("correct horse").is_ok());
+/// assert!(validate_passphrase("").is_err());
+/// ```
+pub fn validate_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.is_empty() {
+        return Err(anyhow!("Passphrase cannot be empty."));
+    }
+
+    Ok(())
+}
+
+/// Reads the passphrase from `WEATHER_CLI_PASSPHRASE`, or prompts for it.
+/// With `confirm`, the prompted passphrase has to be entered twice.
+pub fn read_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_NAME) {
+        validate_passphrase(&passphrase)?;
+        return Ok(passphrase);
+    }
+
+    let passphrase =
+        rpassword::prompt_password("Passphrase: ").context("Failed to read the passphrase.")?;
+    validate_passphrase(&passphrase)?;
+
+    if confirm {
+        let confirmation = rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read the passphrase.")?;
+        if passphrase != confirmation {
+            return Err(anyhow!("Passphrases don't match."));
+        }
+    }
+
+    Ok(passphrase)
+}
@@ -6,15 +6,34 @@ use std::{
 
 use anyhow::{anyhow, Context, Result};
 
+use crate::error::WeatherError;
+
+pub mod api_key;
 pub mod api_usage;
+pub mod cache;
 pub mod cli;
+pub mod clock;
+pub mod daylight;
+pub mod doctor;
+pub mod error;
+pub mod http;
+pub mod icons;
+pub mod key_encryption;
+pub mod meteo;
+pub mod moon;
+pub mod redaction;
+pub mod sun;
+pub mod theme;
+pub mod time_display;
+pub mod types;
+pub mod units;
+pub mod usage;
 pub mod user_setup;
 
 #[cfg(test)]
 mod testing;
 
 mod program_info;
-mod types;
 
 pub mod constants {
     /// JSON file name for an API key.
@@ -23,6 +42,43 @@ pub mod constants {
     /// JSON file name for user setting.
     pub const USER_SETTING_JSON_NAME: &str = "setting";
 
+    /// JSON file name for cached API responses.
+    pub const CACHE_JSON_NAME: &str = "cache";
+
+    /// JSON file name for the API usage ledger.
+    pub const USAGE_JSON_NAME: &str = "usage";
+
+    /// JSON file name for the cool-down periods of API keys.
+    pub const KEY_COOLDOWN_JSON_NAME: &str = "key-cooldown";
+
+    /// JSON file name for the colors of the `custom` theme.
+    pub const THEME_JSON_NAME: &str = "theme";
+
+    /// How long a rejected (401) API key is skipped, in seconds.
+    pub const INVALID_KEY_COOLDOWN_SECONDS: i64 = 3600;
+
+    /// How long a rate limited (429) API key is skipped, in seconds.
+    pub const RATE_LIMITED_KEY_COOLDOWN_SECONDS: i64 = 60;
+
+    /// How many days of API usage records are kept.
+    pub const USAGE_RETENTION_DAYS: i64 = 30;
+
+    /// Default connection timeout in seconds.
+    pub const DEFAULT_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+
+    /// Default timeout for receiving a whole response, in seconds.
+    pub const DEFAULT_READ_TIMEOUT_SECONDS: u64 = 30;
+
+    /// Default number of retries on 429, 5xx and timeouts.
+    pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+    /// Default number of requests allowed per minute.
+    /// (OpenWeather free plan: 60 calls/minute)
+    pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 60;
+
+    /// How long a cached response is served without a new request, in seconds.
+    pub const DEFAULT_CACHE_TTL_SECONDS: u64 = 600;
+
     /// ## Current weather data
     ///
     /// Access current weather data for any location on Earth!
@@ -67,10 +123,10 @@ pub mod constants {
     ///     .replace("{QUERY}", "Toronto")
     ///     .replace("{API_KEY}", "EXAMPLE_KEY");
     ///
-    /// assert_eq!(url, "http://api.openweathermap.org/geo/1.0/direct?q=Toronto&limit=10&appid=EXAMPLE_KEY");
+    /// assert_eq!(url, "https://api.openweathermap.org/geo/1.0/direct?q=Toronto&limit=10&appid=EXAMPLE_KEY");
     /// ```
     pub const GEOLOCATION_API_URL: &str =
-        "http://api.openweathermap.org/geo/1.0/direct?q={QUERY}&limit=10&appid={API_KEY}";
+        "https://api.openweathermap.org/geo/1.0/direct?q={QUERY}&limit=10&appid={API_KEY}";
 }
 
 /// Returns executable directory.
@@ -88,33 +144,30 @@ pub fn get_executable_directory() -> Result<String> {
     Err(anyhow!("Unable to get the executable directory."))
 }
 
-/// Returns `std::fs::File` type value of a JSON file.
-pub fn get_json_file(json_suffix: &str) -> Result<File> {
+/// Returns the path of a JSON file.
+pub fn get_json_file_path(json_suffix: &str) -> Result<String> {
     let executable_dir = get_executable_directory()?;
-
-    let file = match File::open(format!(
+    Ok(format!(
         "{}/{}",
         executable_dir,
         make_json_file_name(json_suffix)
-    )) {
+    ))
+}
+
+/// Returns `std::fs::File` type value of a JSON file.
+pub fn get_json_file(json_suffix: &str) -> Result<File> {
+    let json_file_path = get_json_file_path(json_suffix)?;
+
+    let file = match File::open(&json_file_path) {
         Ok(f) => f,
         Err(_) => {
-            let mut new_file = File::create(format!(
-                "{}/{}",
-                executable_dir,
-                make_json_file_name(json_suffix)
-            ))
-            .context("Failed to create a json file.")?;
+            let mut new_file =
+                File::create(&json_file_path).context("Failed to create a json file.")?;
             new_file
                 .write_all("{}".as_bytes())
                 .context("Failed to create a json file.")?;
 
-            File::open(format!(
-                "{}/{}",
-                executable_dir,
-                make_json_file_name(json_suffix)
-            ))
-            .context("Failed to get the json file.")?
+            File::open(&json_file_path).context("Failed to get the json file.")?
         }
     };
 
@@ -131,75 +184,215 @@ pub fn make_json_file_name(suffix: &str) -> String {
     format!("weather-cli-{}.json", suffix)
 }
 
-pub enum ErrorMessageType {
-    SettingRead,
-    ApiResponseRead,
-    InvalidApiKey,
-}
-
-fn get_file_read_error_message(error_type: ErrorMessageType, context: Option<&str>) -> String {
-    match (error_type, context) {
-        (ErrorMessageType::SettingRead, Some(context)) => {
-            if context == "api" {
-                format!(
-                    "Failed to read {}. Please make sure to setup your API key.",
-                    make_json_file_name(context)
-                )
-            } else {
-                format!("Failed to read the following file: {}", context)
-            }
-        }
-        (ErrorMessageType::ApiResponseRead, Some(context)) => {
-            format!("The given '{}' JSON input may be invalid.", context)
-        }
-        (ErrorMessageType::InvalidApiKey, None) => {
-            "API Key is invalid. Please try again.".to_string()
-        }
-        _ => unreachable!(),
-    }
-}
-
 /// Read a JSON file and return the string.
 pub fn read_json_file<T: serde::de::DeserializeOwned>(json_name: &str) -> Result<T> {
     let mut file = get_json_file(json_name)?;
     let mut json_string = String::new();
     file.read_to_string(&mut json_string)?;
 
-    let api_key_data: T = serde_json::from_str(&json_string).context(
-        get_file_read_error_message(ErrorMessageType::SettingRead, Some(json_name)),
-    )?; // ERROR
+    let api_key_data: T = serde_json::from_str(&json_string).map_err(|_| {
+        if json_name == constants::API_JSON_NAME {
+            WeatherError::ConfigMissing(format!(
+                "Failed to read {}. Please make sure to setup your API key.",
+                make_json_file_name(json_name)
+            ))
+        } else {
+            WeatherError::Parse(format!(
+                "Failed to read the following file: {}",
+                make_json_file_name(json_name)
+            ))
+        }
+    })?;
 
     Ok(api_key_data)
 }
 
-/// Reads a JSON file and returns serialized data.
+/// Deserialize a JSON file without creating it.
+/// Returns `None` when the file doesn't exist.
+pub fn read_existing_json_file<T: serde::de::DeserializeOwned>(
+    json_name: &str,
+) -> Result<Option<T>> {
+    if !std::path::Path::new(&get_json_file_path(json_name)?).exists() {
+        return Ok(None);
+    }
+
+    read_json_file(json_name).map(Some)
+}
+
+/// Serialize data and write it to a JSON file.
+pub fn write_json_file<T: serde::Serialize>(json_name: &str, data: &T) -> Result<()> {
+    let executable_dir = get_executable_directory()?;
+    let json_string = serde_json::to_string(data)?;
+
+    File::create(format!(
+        "{}/{}",
+        executable_dir,
+        make_json_file_name(json_name)
+    ))?
+    .write_all(json_string.as_bytes())
+    .context(format!("Failed to write a JSON file: {}", json_name))?;
+
+    Ok(())
+}
+
+/// Serialize data and write it to a JSON file only the user can read. (mode 600 on Unix)
+/// Used for files containing API keys.
+pub fn write_private_json_file<T: serde::Serialize>(json_name: &str, data: &T) -> Result<()> {
+    let json_string = serde_json::to_string(data)?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(get_json_file_path(json_name)?)
+        .context(format!("Failed to write a JSON file: {}", json_name))?;
+
+    // An existing file keeps its mode when it's opened, so it's restricted here.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(json_string.as_bytes())
+        .context(format!("Failed to write a JSON file: {}", json_name))?;
+
+    Ok(())
+}
+
+/// Returns an error for a failed OpenWeather API response.
+///
+/// The status code is taken from the HTTP status, or from the `cod` field
+/// (a number or a string) of the error envelope when the HTTP status is successful.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::{api_error_from_response, error::WeatherError};
+/// let response = serde_json::json!({ "cod": "404", "message": "city not found" });
+/// let error = api_error_from_response(200, &response).unwrap();
+///
+/// assert!(matches!(error, WeatherError::NotFound { status: Some(404), .. }));
+/// assert!(api_error_from_response(200, &serde_json::json!({ "cod": 200 })).is_none());
+/// ```
+pub fn api_error_from_response(status: u16, response: &serde_json::Value) -> Option<WeatherError> {
+    use serde_json::Value;
+
+    let envelope_code = match response.get("cod") {
+        Some(Value::Number(code)) => code.as_u64().map(|code| code as u16),
+        Some(Value::String(code)) => code.parse::<u16>().ok(),
+        _ => None,
+    };
+    let status = match (status, envelope_code) {
+        (200..=299, Some(code)) => code,
+        (status, _) => status,
+    };
+    let message = response
+        .get("message")
+        .and_then(Value::as_str)
+        .filter(|message| !message.is_empty())
+        .unwrap_or("no details");
+
+    match status {
+        200..=299 => None,
+        401 => Some(WeatherError::InvalidApiKey),
+        404 => Some(WeatherError::NotFound {
+            message: format!("Not found: {}. Please check your city setting.", message),
+            status: Some(status),
+        }),
+        429 => Some(WeatherError::RateLimited {
+            message: format!(
+                "OpenWeather rate limit exceeded: {}. Please wait a minute and try again.",
+                message
+            ),
+            status: Some(status),
+        }),
+        500..=599 => Some(WeatherError::Network {
+            message: format!(
+                "OpenWeather is unavailable ({}): {}. Please try again later.",
+                status, message
+            ),
+            status: Some(status),
+        }),
+        _ => Some(WeatherError::Api {
+            message: format!("OpenWeather rejected the request ({}): {}", status, message),
+            status,
+        }),
+    }
+}
+
+/// Reads an API response and returns serialized data.
+/// Failed responses are turned into a `WeatherError`.
 pub fn read_json_response<T: serde::de::DeserializeOwned>(
+    status: u16,
     response: &str,
-    error_message_type: ErrorMessageType,
     error_context: &str,
 ) -> Result<T> {
     use serde_json::Value;
 
-    let api_response: Value = serde_json::from_str(response).context(
-        get_file_read_error_message(ErrorMessageType::ApiResponseRead, Some(error_context)),
-    )?;
+    let invalid_input_error = || {
+        WeatherError::Parse(format!(
+            "The given '{}' JSON input may be invalid.",
+            error_context
+        ))
+    };
 
-    // Invalid API key error.
-    if let Some(401) = api_response["cod"].as_i64() {
-        return Err(anyhow!(get_file_read_error_message(
-            ErrorMessageType::InvalidApiKey,
-            None
-        )));
+    let api_response: Value = match serde_json::from_str(response) {
+        Ok(value) => value,
+        // Error pages from proxies and load balancers aren't JSON.
+        Err(_) => {
+            return Err(api_error_from_response(status, &Value::Null)
+                .unwrap_or_else(invalid_input_error)
+                .into())
+        }
+    };
+
+    if let Some(error) = api_error_from_response(status, &api_response) {
+        return Err(error.into());
     }
 
-    let response_data: T = serde_json::from_str(response).context(get_file_read_error_message(
-        error_message_type,
-        Some(error_context),
-    ))?;
+    let response_data: T =
+        serde_json::from_value(api_response).map_err(|_| invalid_input_error())?;
 
     Ok(response_data)
 }
 
+/// Returns the current weather API URL for the given coordinates.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::make_weather_api_url;
+/// let url = make_weather_api_url(37.3361663, -121.890591, "EXAMPLE_KEY", "imperial");
+///
+/// assert_eq!(url, "https://api.openweathermap.org/data/2.5/weather?lat=37.3361663&lon=-121.890591&appid=EXAMPLE_KEY&units=imperial");
+/// ```
+pub fn make_weather_api_url(lat: f64, lon: f64, api_key: &str, units: &str) -> String {
+    replace_url_placeholders(
+        constants::WEATHER_API_URL,
+        &[
+            URLPlaceholder {
+                placeholder: "{LAT_VALUE}".to_string(),
+                value: lat.to_string(),
+            },
+            URLPlaceholder {
+                placeholder: "{LON_VALUE}".to_string(),
+                value: lon.to_string(),
+            },
+            URLPlaceholder {
+                placeholder: "{API_KEY}".to_string(),
+                value: api_key.to_string(),
+            },
+            URLPlaceholder {
+                placeholder: "{UNIT}".to_string(),
+                value: units.to_string(),
+            },
+        ],
+    )
+}
+
 /// URL placeholder information.
 ///
 /// ## Example Usage
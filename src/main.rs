@@ -1,6 +1,8 @@
+use std::process::ExitCode;
+
 use weather_cli::cli;
 
 #[tokio::main]
-async fn main() {
-    cli::init().await;
+async fn main() -> ExitCode {
+    cli::init().await
 }
@@ -0,0 +1,115 @@
+// Derived meteorological quantities.
+// Every function takes SI values (kelvin, %, m/s) and returns temperatures in kelvin.
+
+const KELVIN_OFFSET: f64 = 273.15;
+
+fn kelvin_to_celsius(kelvin: f64) -> f64 {
+    kelvin - KELVIN_OFFSET
+}
+
+fn kelvin_to_fahrenheit(kelvin: f64) -> f64 {
+    kelvin_to_celsius(kelvin) * 9.0 / 5.0 + 32.0
+}
+
+fn fahrenheit_to_kelvin(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0 + KELVIN_OFFSET
+}
+
+/// Dew point with the Magnus formula. (Sonntag 1990 constants)
+///
+/// ## Example
+/// ```
+/// # use weather_cli::meteo::dew_point;
+/// let dew_point = dew_point(293.15, 50.0) - 273.15;
+/// assert!((dew_point - 9.3).abs() < 0.05);
+/// ```
+pub fn dew_point(temperature: f64, humidity: f64) -> f64 {
+    const B: f64 = 17.62;
+    const C: f64 = 243.12;
+
+    let celsius = kelvin_to_celsius(temperature);
+    let gamma = (humidity.max(1.0) / 100.0).ln() + B * celsius / (C + celsius);
+    C * gamma / (B - gamma) + KELVIN_OFFSET
+}
+
+/// Heat index with the NWS algorithm. (Rothfusz regression with adjustments)
+/// Returns `None` below 80 °F, where the heat index isn't defined.
+pub fn heat_index(temperature: f64, humidity: f64) -> Option<f64> {
+    let t = kelvin_to_fahrenheit(temperature);
+    let rh = humidity;
+    if t < 80.0 {
+        return None;
+    }
+
+    // The simple formula is used when its result is below 80 °F.
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if simple < 80.0 {
+        return Some(fahrenheit_to_kelvin(simple));
+    }
+
+    let mut index = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+        - 0.224_755_41 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+    }
+
+    Some(fahrenheit_to_kelvin(index))
+}
+
+/// Wind chill with the NWS / Environment Canada formula.
+/// Returns `None` above 10 °C or below 4.8 km/h, where it isn't defined.
+pub fn wind_chill(temperature: f64, wind_speed: f64) -> Option<f64> {
+    let t = kelvin_to_celsius(temperature);
+    let v = wind_speed * 3.6;
+    if t > 10.0 || v < 4.8 {
+        return None;
+    }
+
+    let v_power = v.powf(0.16);
+    Some(13.12 + 0.6215 * t - 11.37 * v_power + 0.3965 * t * v_power + KELVIN_OFFSET)
+}
+
+/// Humidex of Environment Canada, from the temperature and the dew point.
+pub fn humidex(temperature: f64, dew_point: f64) -> f64 {
+    let vapour_pressure = 6.11 * (5417.7530 * (1.0 / 273.16 - 1.0 / dew_point)).exp();
+    temperature + 0.5555 * (vapour_pressure - 10.0)
+}
+
+/// Apparent temperature of the Australian Bureau of Meteorology. (Steadman 1994, without radiation)
+pub fn apparent_temperature(temperature: f64, humidity: f64, wind_speed: f64) -> f64 {
+    let t = kelvin_to_celsius(temperature);
+    let vapour_pressure = humidity / 100.0 * 6.105 * (17.27 * t / (237.7 + t)).exp();
+    t + 0.33 * vapour_pressure - 0.70 * wind_speed - 4.00 + KELVIN_OFFSET
+}
+
+/// Derived quantities of a weather observation. Temperatures are in kelvin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DerivedQuantities {
+    pub dew_point: f64,
+    pub heat_index: Option<f64>,
+    pub wind_chill: Option<f64>,
+    pub humidex: f64,
+    pub apparent_temperature: f64,
+}
+
+impl DerivedQuantities {
+    /// Computes every derived quantity from SI values.
+    pub fn new(temperature: f64, humidity: f64, wind_speed: f64) -> DerivedQuantities {
+        let dew_point = dew_point(temperature, humidity);
+
+        DerivedQuantities {
+            dew_point,
+            heat_index: heat_index(temperature, humidity),
+            wind_chill: wind_chill(temperature, wind_speed),
+            humidex: humidex(temperature, dew_point),
+            apparent_temperature: apparent_temperature(temperature, humidity, wind_speed),
+        }
+    }
+}
@@ -0,0 +1,391 @@
+// Local moon calculator. No API requests are made.
+// New and full moons use Meeus, "Astronomical Algorithms" chapter 49 (within a minute or two),
+// and the moon's position uses the low-precision series of Montenbruck & Pfleger. (a few arcminutes)
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+
+use crate::{api_usage::OutputFormat, clock::Clock};
+
+/// Mean length of a lunar month in days.
+pub const SYNODIC_MONTH: f64 = 29.530588861;
+
+/// Altitude of the moon's center at moonrise and moonset, with the parallax, refraction and radius.
+const MOONRISE_ALTITUDE: f64 = 0.133;
+
+/// Approximate difference between the terrestrial time of Meeus and UTC, in seconds.
+const DELTA_T_SECONDS: f64 = 69.0;
+
+fn julian_day(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 / 86400.0 + 2440587.5
+}
+
+fn from_julian_day(julian_day: f64) -> DateTime<Utc> {
+    DateTime::<Utc>::from_timestamp(((julian_day - 2440587.5) * 86400.0).round() as i64, 0)
+        .unwrap_or_default()
+}
+
+/// Returns the time of the `k`th new moon since January 2000, or the full moon with `k + 0.5`.
+fn lunar_phase_time(k: f64) -> DateTime<Utc> {
+    let full = k.fract() != 0.0;
+    let t = k / 1236.85;
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t * t;
+    let jde = 2451550.09766 + SYNODIC_MONTH * k + 0.00015437 * t * t - 0.000000150 * t.powi(3)
+        + 0.00000000073 * t.powi(4);
+
+    let m = (2.5534 + 29.10535670 * k - 0.0000014 * t * t).to_radians();
+    let mp =
+        (201.5643 + 385.81693528 * k + 0.0107582 * t * t + 0.00001238 * t.powi(3)).to_radians();
+    let f = (160.7108 + 390.67050284 * k - 0.0016118 * t * t - 0.00000227 * t.powi(3)).to_radians();
+    let omega = (124.7746 - 1.56375588 * k + 0.0020672 * t * t).to_radians();
+
+    // The first terms differ between new and full moons.
+    let (c1, c2, c3, c4, c5, c6, c7) = match full {
+        false => (
+            -0.40720, 0.17241, 0.01608, 0.01039, 0.00739, -0.00514, 0.00208,
+        ),
+        true => (
+            -0.40614, 0.17302, 0.01614, 0.01043, 0.00734, -0.00515, 0.00209,
+        ),
+    };
+    let correction = c1 * mp.sin()
+        + c2 * e * m.sin()
+        + c3 * (2.0 * mp).sin()
+        + c4 * (2.0 * f).sin()
+        + c5 * e * (mp - m).sin()
+        + c6 * e * (mp + m).sin()
+        + c7 * e * e * (2.0 * m).sin()
+        - 0.00111 * (mp - 2.0 * f).sin()
+        - 0.00057 * (mp + 2.0 * f).sin()
+        + 0.00056 * e * (2.0 * mp + m).sin()
+        - 0.00042 * (3.0 * mp).sin()
+        + 0.00042 * e * (m + 2.0 * f).sin()
+        + 0.00038 * e * (m - 2.0 * f).sin()
+        - 0.00024 * e * (2.0 * mp - m).sin()
+        - 0.00017 * omega.sin()
+        - 0.00007 * (mp + 2.0 * m).sin()
+        + 0.00004 * (2.0 * mp - 2.0 * f).sin()
+        + 0.00004 * (3.0 * m).sin()
+        + 0.00003 * (mp + m - 2.0 * f).sin()
+        + 0.00003 * (2.0 * mp + 2.0 * f).sin()
+        - 0.00003 * (mp + m + 2.0 * f).sin()
+        + 0.00003 * (mp - m + 2.0 * f).sin()
+        - 0.00002 * (mp - m - 2.0 * f).sin()
+        - 0.00002 * (3.0 * mp + m).sin()
+        + 0.00002 * (4.0 * mp).sin();
+
+    from_julian_day(jde + correction - DELTA_T_SECONDS / 86400.0)
+}
+
+/// Returns the first new moon (`full` is false) or full moon after a time.
+pub fn next_lunar_phase(time: DateTime<Utc>, full: bool) -> DateTime<Utc> {
+    let offset = if full { 0.5 } else { 0.0 };
+    let mut k = ((julian_day(time) - 2451550.09766) / SYNODIC_MONTH).floor() - 1.0;
+    while lunar_phase_time(k + offset) <= time {
+        k += 1.0;
+    }
+    lunar_phase_time(k + offset)
+}
+
+/// Returns the last new moon at or before a time.
+pub fn previous_new_moon(time: DateTime<Utc>) -> DateTime<Utc> {
+    let mut k = ((julian_day(time) - 2451550.09766) / SYNODIC_MONTH).floor() + 1.0;
+    while lunar_phase_time(k) > time {
+        k -= 1.0;
+    }
+    lunar_phase_time(k)
+}
+
+/// Returns the ecliptic longitude and latitude of the moon in radians.
+fn moon_ecliptic(time: DateTime<Utc>) -> (f64, f64) {
+    use std::f64::consts::TAU;
+
+    const ARCSECONDS: f64 = 206264.8062;
+    let t = (julian_day(time) - 2451545.0) / 36525.0;
+    let revolutions = |value: f64| value.rem_euclid(1.0);
+
+    let mean_longitude = revolutions(0.606433 + 1336.855225 * t);
+    let l = TAU * revolutions(0.374897 + 1325.552410 * t);
+    let ls = TAU * revolutions(0.993133 + 99.997361 * t);
+    let d = TAU * revolutions(0.827361 + 1236.853086 * t);
+    let f = TAU * revolutions(0.259086 + 1342.227825 * t);
+
+    let longitude_perturbation = 22640.0 * l.sin() - 4586.0 * (l - 2.0 * d).sin()
+        + 2370.0 * (2.0 * d).sin()
+        + 769.0 * (2.0 * l).sin()
+        - 668.0 * ls.sin()
+        - 412.0 * (2.0 * f).sin()
+        - 212.0 * (2.0 * l - 2.0 * d).sin()
+        - 206.0 * (l + ls - 2.0 * d).sin()
+        + 192.0 * (l + 2.0 * d).sin()
+        - 165.0 * (ls - 2.0 * d).sin()
+        - 125.0 * d.sin()
+        - 110.0 * (l + ls).sin()
+        + 148.0 * (l - ls).sin()
+        - 55.0 * (2.0 * f - 2.0 * d).sin();
+
+    let s = f + (longitude_perturbation + 412.0 * (2.0 * f).sin() + 541.0 * ls.sin()) / ARCSECONDS;
+    let h = f - 2.0 * d;
+    let n = -526.0 * h.sin() + 44.0 * (l + h).sin() - 31.0 * (h - l).sin() - 23.0 * (ls + h).sin()
+        + 11.0 * (h - ls).sin()
+        - 25.0 * (f - 2.0 * l).sin()
+        + 21.0 * (f - l).sin();
+
+    let longitude = TAU * revolutions(mean_longitude + longitude_perturbation / 1296000.0);
+    let latitude = (18520.0 * s.sin() + n) / ARCSECONDS;
+    (longitude, latitude)
+}
+
+/// Returns the ecliptic longitude of the sun in radians.
+fn sun_longitude(time: DateTime<Utc>) -> f64 {
+    let days = julian_day(time) - 2451545.0;
+    let mean_longitude = 280.460 + 0.9856474 * days;
+    let mean_anomaly = (357.528 + 0.9856003 * days).to_radians();
+    (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin()).to_radians()
+}
+
+/// Returns the altitude of the moon's center in degrees, without refraction.
+fn moon_altitude(time: DateTime<Utc>, latitude: f64, longitude: f64) -> f64 {
+    let (moon_longitude, moon_latitude) = moon_ecliptic(time);
+    let days = julian_day(time) - 2451545.0;
+    let obliquity = (23.43929111 - 0.0130042 * days / 36525.0).to_radians();
+
+    let right_ascension = (moon_longitude.sin() * obliquity.cos()
+        - moon_latitude.tan() * obliquity.sin())
+    .atan2(moon_longitude.cos());
+    let declination = (moon_latitude.sin() * obliquity.cos()
+        + moon_latitude.cos() * obliquity.sin() * moon_longitude.sin())
+    .asin();
+
+    let sidereal_time = (280.46061837 + 360.98564736629 * days + longitude).to_radians();
+    let hour_angle = sidereal_time - right_ascension;
+    let latitude = latitude.to_radians();
+
+    (latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos())
+        .asin()
+        .to_degrees()
+}
+
+/// Names of the moon phases.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoonPhase {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    /// Returns the phase of an elongation (the moon's longitude minus the sun's) in degrees.
+    /// Each phase covers 45°, centered on 0° for the new moon.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::moon::MoonPhase;
+    /// assert_eq!(MoonPhase::from_elongation(350.0), MoonPhase::NewMoon);
+    /// assert_eq!(MoonPhase::from_elongation(95.0), MoonPhase::FirstQuarter);
+    /// assert_eq!(MoonPhase::from_elongation(200.0), MoonPhase::FullMoon);
+    /// ```
+    pub fn from_elongation(elongation: f64) -> MoonPhase {
+        const PHASES: [MoonPhase; 8] = [
+            MoonPhase::NewMoon,
+            MoonPhase::WaxingCrescent,
+            MoonPhase::FirstQuarter,
+            MoonPhase::WaxingGibbous,
+            MoonPhase::FullMoon,
+            MoonPhase::WaningGibbous,
+            MoonPhase::LastQuarter,
+            MoonPhase::WaningCrescent,
+        ];
+        PHASES[((elongation.rem_euclid(360.0) + 22.5) / 45.0) as usize % 8]
+    }
+}
+
+impl std::fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            MoonPhase::NewMoon => "New Moon",
+            MoonPhase::WaxingCrescent => "Waxing Crescent",
+            MoonPhase::FirstQuarter => "First Quarter",
+            MoonPhase::WaxingGibbous => "Waxing Gibbous",
+            MoonPhase::FullMoon => "Full Moon",
+            MoonPhase::WaningGibbous => "Waning Gibbous",
+            MoonPhase::LastQuarter => "Last Quarter",
+            MoonPhase::WaningCrescent => "Waning Crescent",
+        })
+    }
+}
+
+/// The moon at a time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MoonInfo {
+    pub phase: MoonPhase,
+    /// Illuminated fraction of the disk, from 0 to 1.
+    pub illumination: f64,
+    /// Days since the last new moon.
+    pub age: f64,
+    pub next_new_moon: DateTime<Utc>,
+    pub next_full_moon: DateTime<Utc>,
+}
+
+impl MoonInfo {
+    pub fn new(time: DateTime<Utc>) -> MoonInfo {
+        let (moon_longitude, moon_latitude) = moon_ecliptic(time);
+        let elongation = (moon_longitude - sun_longitude(time)).to_degrees();
+        // The phase angle is close to 180° minus the angle between the sun and the moon.
+        let cos_phase_angle = -moon_latitude.cos() * elongation.to_radians().cos();
+
+        MoonInfo {
+            phase: MoonPhase::from_elongation(elongation),
+            illumination: (1.0 + cos_phase_angle) / 2.0,
+            age: (time - previous_new_moon(time)).num_seconds() as f64 / 86400.0,
+            next_new_moon: next_lunar_phase(time, false),
+            next_full_moon: next_lunar_phase(time, true),
+        }
+    }
+}
+
+/// Moonrise and moonset of a date. `None` if the moon doesn't rise or set that day.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MoonRiseSet {
+    pub rise: Option<DateTime<Utc>>,
+    pub set: Option<DateTime<Utc>>,
+}
+
+/// Returns moonrise and moonset of a date in a UTC offset, searched in 10-minute steps.
+pub fn moon_rise_set(
+    date: NaiveDate,
+    offset: FixedOffset,
+    latitude: f64,
+    longitude: f64,
+) -> MoonRiseSet {
+    let altitude =
+        |time: DateTime<Utc>| moon_altitude(time, latitude, longitude) - MOONRISE_ALTITUDE;
+    let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+        - Duration::seconds(offset.local_minus_utc() as i64);
+    let step = Duration::minutes(10);
+
+    let mut result = MoonRiseSet {
+        rise: None,
+        set: None,
+    };
+    let mut previous = (start, altitude(start));
+    for index in 1..=144 {
+        let time = start + step * index;
+        let current = (time, altitude(time));
+
+        if (previous.1 < 0.0) != (current.1 < 0.0) {
+            // Bisects to the second.
+            let (mut low, mut high) = (previous, current);
+            while (high.0 - low.0).num_seconds() > 1 {
+                let middle = low.0 + Duration::seconds((high.0 - low.0).num_seconds() / 2);
+                let middle = (middle, altitude(middle));
+                match (middle.1 < 0.0) == (low.1 < 0.0) {
+                    true => low = middle,
+                    false => high = middle,
+                }
+            }
+
+            match previous.1 < 0.0 {
+                true => result.rise = result.rise.or(Some(high.0)),
+                false => result.set = result.set.or(Some(high.0)),
+            }
+        }
+        previous = current;
+    }
+
+    result
+}
+
+/// Prints the moon phase, and moonrise and moonset of a date in the saved city.
+/// (default: today in the city)
+pub fn print_moon_information(
+    date: Option<NaiveDate>,
+    output: OutputFormat,
+    clock: &impl Clock,
+) -> Result<()> {
+    use chrono::{Local, Offset};
+
+    use crate::{
+        constants::USER_SETTING_JSON_NAME, error::WeatherError, read_json_file,
+        sun::cached_utc_offset, time_display::TimeFormatter, types::user_settings::UserSetting,
+    };
+
+    let setting = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    let city = setting.city.as_ref().ok_or_else(|| {
+        WeatherError::ConfigMissing(
+            "Failed to read user setting! Please run 'set-location' command to configure settings."
+                .to_string(),
+        )
+    })?;
+
+    let now = clock.now();
+    let city_offset = cached_utc_offset(&setting);
+    let offset = city_offset.unwrap_or_else(|| now.with_timezone(&Local).offset().fix());
+
+    // The phase of another date is calculated at its noon.
+    let (date, time) = match date {
+        Some(date) => (
+            date,
+            date.and_hms_opt(12, 0, 0).unwrap_or_default().and_utc()
+                - Duration::seconds(offset.local_minus_utc() as i64),
+        ),
+        None => (now.with_timezone(&offset).date_naive(), now),
+    };
+    let moon = MoonInfo::new(time);
+    let rise_set = moon_rise_set(date, offset, city.lat, city.lon);
+
+    if output == OutputFormat::Json {
+        let time =
+            |time: Option<DateTime<Utc>>| time.map(|time| time.with_timezone(&offset).to_rfc3339());
+        let output = serde_json::json!({
+            "city": { "name": city.name, "country": city.country, "lat": city.lat, "lon": city.lon },
+            "date": date.to_string(),
+            "utc_offset": offset.to_string(),
+            "phase": moon.phase.to_string(),
+            "illumination": (moon.illumination * 1000.0).round() / 10.0,
+            "age": (moon.age * 100.0).round() / 100.0,
+            "moonrise": time(rise_set.rise),
+            "moonset": time(rise_set.set),
+            "next_new_moon": time(Some(moon.next_new_moon)),
+            "next_full_moon": time(Some(moon.next_full_moon)),
+        });
+
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let time_formatter = TimeFormatter::new(&setting.time);
+    let time = |time: Option<DateTime<Utc>>| match time {
+        Some(time) => time_formatter.time(&time.with_timezone(&offset)),
+        None => "none on this date".to_string(),
+    };
+    let date_time = |time: DateTime<Utc>| {
+        let time = time.with_timezone(&offset);
+        format!("{} {}", time.format("%Y-%m-%d"), time_formatter.time(&time))
+    };
+
+    println!(
+        "\n{} ({}) / {} (UTC{})",
+        city.name, city.country, date, offset
+    );
+    if city_offset.is_none() {
+        println!("* Times are in your time zone. Run \"weather-cli check\" to use the city's.");
+    }
+    println!(
+        "\n- Phase: {} ({:.0} % illuminated)",
+        moon.phase,
+        moon.illumination * 100.0
+    );
+    println!("- Age: {:.1} days", moon.age);
+    println!("- Moonrise: {}", time(rise_set.rise));
+    println!("- Moonset: {}", time(rise_set.set));
+    println!("- Next full moon: {}", date_time(moon.next_full_moon));
+    println!("- Next new moon: {}", date_time(moon.next_new_moon));
+
+    Ok(())
+}
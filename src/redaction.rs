@@ -0,0 +1,61 @@
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+
+/// Replacement text of redacted secrets.
+pub const REDACTED: &str = "***";
+
+/// Secrets that must never be displayed.
+static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Registers a secret to be removed by `redact`.
+pub fn register_secret(secret: &str) {
+    if secret.is_empty() {
+        return;
+    }
+
+    let mut secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    if !secrets.iter().any(|registered| registered == secret) {
+        secrets.push(secret.to_string());
+    }
+}
+
+/// Stops redacting a secret registered with `register_secret`.
+pub fn unregister_secret(secret: &str) {
+    let mut secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    secrets.retain(|registered| registered != secret);
+}
+
+/// Removes registered secrets and `appid` URL parameters from a text.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::redaction::{redact, register_secret, unregister_secret};
+/// let url = "https://api.openweathermap.org/data/2.5/weather?lat=0&lon=0&appid=EXAMPLE_KEY&units=metric";
+/// assert_eq!(
+///     redact(url),
+///     "https://api.openweathermap.org/data/2.5/weather?lat=0&lon=0&appid=***&units=metric"
+/// );
+///
+/// register_secret("EXAMPLE_SECRET");
+/// assert_eq!(redact("The key is EXAMPLE_SECRET."), "The key is ***.");
+///
+/// unregister_secret("EXAMPLE_SECRET");
+/// assert_eq!(redact("The key is EXAMPLE_SECRET."), "The key is EXAMPLE_SECRET.");
+/// ```
+pub fn redact(text: &str) -> String {
+    static APPID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+    let appid_regex =
+        APPID_REGEX.get_or_init(|| Regex::new(r#"(?i)(appid=)[^&\s)"']+"#).expect("valid regex"));
+    let mut redacted = appid_regex
+        .replace_all(text, format!("${{1}}{}", REDACTED))
+        .into_owned();
+
+    let secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    for secret in secrets.iter() {
+        redacted = redacted.replace(secret, REDACTED);
+    }
+
+    redacted
+}
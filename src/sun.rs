@@ -0,0 +1,414 @@
+// Local solar calculator with the NOAA algorithm. No API requests are made.
+// (https://gml.noaa.gov/grad/solcalc/calcdetails.html)
+// Times are accurate to about a minute between latitudes of ±72°.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Timelike, Utc};
+
+use crate::{api_usage::OutputFormat, clock::Clock};
+
+/// Altitude of the sun's center at sunrise and sunset, with the refraction and the sun's radius.
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+pub const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+pub const NAUTICAL_TWILIGHT_ALTITUDE: f64 = -12.0;
+pub const ASTRONOMICAL_TWILIGHT_ALTITUDE: f64 = -18.0;
+/// Golden hour is while the sun is between -4° and 6°.
+pub const GOLDEN_HOUR_ALTITUDE: f64 = 6.0;
+/// Blue hour is while the sun is between -6° and -4°.
+pub const BLUE_HOUR_ALTITUDE: f64 = -4.0;
+
+/// Returns Julian centuries since J2000.0.
+fn julian_century(time: DateTime<Utc>) -> f64 {
+    let julian_day = time.timestamp() as f64 / 86400.0 + 2440587.5;
+    (julian_day - 2451545.0) / 36525.0
+}
+
+/// Returns the solar declination in degrees, and the equation of time in minutes.
+fn solar_coordinates(time: DateTime<Utc>) -> (f64, f64) {
+    let t = julian_century(time);
+
+    let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.0);
+    let mean_anomaly = 357.52911 + t * (35999.05029 - 0.0001537 * t);
+    let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+    let m = mean_anomaly.to_radians();
+    let equation_of_center = m.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + (2.0 * m).sin() * (0.019993 - 0.000101 * t)
+        + (3.0 * m).sin() * 0.000289;
+
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let apparent_longitude = mean_longitude + equation_of_center - 0.00569 - 0.00478 * omega.sin();
+    let mean_obliquity =
+        23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.00256 * omega.cos()).to_radians();
+
+    let declination = (obliquity.sin() * apparent_longitude.to_radians().sin())
+        .asin()
+        .to_degrees();
+
+    let y = (obliquity / 2.0).tan().powi(2);
+    let l = mean_longitude.to_radians();
+    let equation_of_time = 4.0
+        * (y * (2.0 * l).sin() - 2.0 * eccentricity * m.sin()
+            + 4.0 * eccentricity * y * m.sin() * (2.0 * l).cos()
+            - 0.5 * y * y * (4.0 * l).sin()
+            - 1.25 * eccentricity * eccentricity * (2.0 * m).sin())
+        .to_degrees();
+
+    (declination, equation_of_time)
+}
+
+/// Returns the hour angle in degrees when the sun is at `altitude`,
+/// or `None` if the sun doesn't reach it.
+fn hour_angle(latitude: f64, declination: f64, altitude: f64) -> Option<f64> {
+    let (latitude, declination) = (latitude.to_radians(), declination.to_radians());
+    let cos_hour_angle = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+
+    (-1.0..=1.0)
+        .contains(&cos_hour_angle)
+        .then(|| cos_hour_angle.acos().to_degrees())
+}
+
+/// Returns the time of a date (UTC midnight) plus minutes, rounded to seconds.
+fn at_minutes(date: NaiveDate, minutes: f64) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+        + Duration::seconds((minutes * 60.0).round() as i64)
+}
+
+/// Returns the solar noon of a date in the city.
+pub fn solar_noon(date: NaiveDate, longitude: f64) -> DateTime<Utc> {
+    let mut noon = at_minutes(date, 720.0 - 4.0 * longitude);
+    for _ in 0..2 {
+        let (_, equation_of_time) = solar_coordinates(noon);
+        noon = at_minutes(date, 720.0 - 4.0 * longitude - equation_of_time);
+    }
+    noon
+}
+
+/// Returns the time when the sun crosses `altitude` in the morning (`rising`) or the evening.
+/// `None` if the sun stays above or below it all day.
+pub fn crossing_time(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+    rising: bool,
+) -> Option<DateTime<Utc>> {
+    let sign = if rising { -1.0 } else { 1.0 };
+
+    // The declination and the equation of time are refined at the crossing time.
+    let mut time = solar_noon(date, longitude);
+    for _ in 0..3 {
+        let (declination, equation_of_time) = solar_coordinates(time);
+        let hour_angle = hour_angle(latitude, declination, altitude)?;
+        time = at_minutes(
+            date,
+            720.0 - 4.0 * longitude - equation_of_time + sign * 4.0 * hour_angle,
+        );
+    }
+    Some(time)
+}
+
+/// Position of the sun in degrees. The elevation includes the atmospheric refraction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SunPosition {
+    pub elevation: f64,
+    /// Clockwise from north.
+    pub azimuth: f64,
+}
+
+/// Returns the position of the sun at a time.
+pub fn sun_position(time: DateTime<Utc>, latitude: f64, longitude: f64) -> SunPosition {
+    let (declination, equation_of_time) = solar_coordinates(time);
+
+    let minutes = time.num_seconds_from_midnight() as f64 / 60.0;
+    let true_solar_time = (minutes + equation_of_time + 4.0 * longitude).rem_euclid(1440.0);
+    let hour_angle = true_solar_time / 4.0 - 180.0;
+
+    let (lat, decl) = (latitude.to_radians(), declination.to_radians());
+    let cos_zenith = (lat.sin() * decl.sin()
+        + lat.cos() * decl.cos() * hour_angle.to_radians().cos())
+    .clamp(-1.0, 1.0);
+    let zenith = cos_zenith.acos();
+    let elevation = 90.0 - zenith.to_degrees();
+
+    let tan_elevation = elevation.to_radians().tan();
+    let refraction = match elevation {
+        e if e > 85.0 => 0.0,
+        e if e > 5.0 => {
+            58.1 / tan_elevation - 0.07 / tan_elevation.powi(3) + 0.000086 / tan_elevation.powi(5)
+        }
+        e if e > -0.575 => 1735.0 + e * (-518.2 + e * (103.4 + e * (-12.79 + e * 0.711))),
+        _ => -20.772 / tan_elevation,
+    } / 3600.0;
+
+    let azimuth_denominator = lat.cos() * zenith.sin();
+    let azimuth = if azimuth_denominator.abs() > 0.001 {
+        let cos_azimuth =
+            ((lat.sin() * zenith.cos() - decl.sin()) / azimuth_denominator).clamp(-1.0, 1.0);
+        let azimuth = 180.0 - cos_azimuth.acos().to_degrees();
+        match hour_angle > 0.0 {
+            true => (-azimuth).rem_euclid(360.0),
+            false => azimuth.rem_euclid(360.0),
+        }
+    } else if latitude > 0.0 {
+        180.0
+    } else {
+        0.0
+    };
+
+    SunPosition {
+        elevation: elevation + refraction,
+        azimuth,
+    }
+}
+
+/// Times when the sun crosses an altitude in the morning and the evening.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crossing {
+    pub dawn: Option<DateTime<Utc>>,
+    pub dusk: Option<DateTime<Utc>>,
+}
+
+/// Sun events of a date in the city.
+pub struct SunDay {
+    pub solar_noon: DateTime<Utc>,
+    /// Sunrise and sunset.
+    pub sun: Crossing,
+    pub civil: Crossing,
+    pub nautical: Crossing,
+    pub astronomical: Crossing,
+    /// Morning and evening windows. Windows around midnight aren't included.
+    pub golden_hours: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    pub blue_hours: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Whether the sun stays above the horizon all day, when it doesn't rise or set.
+    pub polar_day: Option<bool>,
+}
+
+impl SunDay {
+    pub fn new(date: NaiveDate, latitude: f64, longitude: f64) -> SunDay {
+        let crossing = |altitude: f64| Crossing {
+            dawn: crossing_time(date, latitude, longitude, altitude, true),
+            dusk: crossing_time(date, latitude, longitude, altitude, false),
+        };
+        // The sun is between `low` and `high` after `low` in the morning until `high`,
+        // and after `high` in the evening until `low`. If it never reaches `high`, all day.
+        let windows = |low: Crossing, high: Crossing| match (low, high) {
+            (
+                Crossing {
+                    dawn: Some(low_dawn),
+                    dusk: Some(low_dusk),
+                },
+                Crossing {
+                    dawn: Some(high_dawn),
+                    dusk: Some(high_dusk),
+                },
+            ) => vec![(low_dawn, high_dawn), (high_dusk, low_dusk)],
+            (
+                Crossing {
+                    dawn: Some(low_dawn),
+                    dusk: Some(low_dusk),
+                },
+                _,
+            ) => vec![(low_dawn, low_dusk)],
+            _ => Vec::new(),
+        };
+
+        let noon = solar_noon(date, longitude);
+        let sun = crossing(SUNRISE_ALTITUDE);
+        let civil = crossing(CIVIL_TWILIGHT_ALTITUDE);
+        let blue_hour = crossing(BLUE_HOUR_ALTITUDE);
+        let golden_hour = crossing(GOLDEN_HOUR_ALTITUDE);
+        let polar_day = sun
+            .dawn
+            .is_none()
+            .then(|| sun_position(noon, latitude, longitude).elevation > SUNRISE_ALTITUDE);
+
+        SunDay {
+            solar_noon: noon,
+            sun,
+            nautical: crossing(NAUTICAL_TWILIGHT_ALTITUDE),
+            astronomical: crossing(ASTRONOMICAL_TWILIGHT_ALTITUDE),
+            golden_hours: windows(blue_hour, golden_hour),
+            blue_hours: windows(civil, blue_hour),
+            civil,
+            polar_day,
+        }
+    }
+
+    /// Time between sunrise and sunset. 24 hours in polar day, and zero in polar night.
+    pub fn day_length(&self) -> Duration {
+        match (self.sun.dawn, self.sun.dusk, self.polar_day) {
+            (Some(sunrise), Some(sunset), _) => sunset - sunrise,
+            (_, _, Some(true)) => Duration::hours(24),
+            _ => Duration::zero(),
+        }
+    }
+}
+
+/// Formats a signed duration in minutes and seconds. (ex. `-2m 31s`)
+///
+/// ## Example
+/// ```
+/// # use weather_cli::sun::format_change;
+/// # use chrono::Duration;
+/// assert_eq!(format_change(Duration::seconds(-151)), "-2m 31s");
+/// assert_eq!(format_change(Duration::seconds(45)), "+0m 45s");
+/// ```
+pub fn format_change(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("{}{}m {}s", sign, seconds.abs() / 60, seconds.abs() % 60)
+}
+
+/// Returns the UTC offset of the city from the cached weather response,
+/// since the saved city has no time zone.
+pub fn cached_utc_offset(
+    setting: &crate::types::user_settings::UserSetting,
+) -> Option<FixedOffset> {
+    use crate::cache::{read_cache_entry, CacheKey};
+
+    let city = setting.city.as_ref()?;
+    let cache_key = CacheKey {
+        provider: "openweather",
+        endpoint: "weather",
+        lat: city.lat,
+        lon: city.lon,
+        units: setting.units.as_ref()?,
+    };
+    let entry = read_cache_entry(&cache_key).ok()??;
+    let response: serde_json::Value = serde_json::from_str(&entry.body).ok()?;
+
+    FixedOffset::east_opt(response.get("timezone")?.as_i64()? as i32)
+}
+
+/// Prints sun events of a date in the saved city. (default: today in the city)
+pub fn print_sun_information(
+    date: Option<NaiveDate>,
+    output: OutputFormat,
+    clock: &impl Clock,
+) -> Result<()> {
+    use chrono::{Local, Offset};
+
+    use crate::{
+        constants::USER_SETTING_JSON_NAME, error::WeatherError, read_json_file,
+        time_display::TimeFormatter, types::user_settings::UserSetting, units::compass_direction,
+    };
+
+    let setting = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    let city = setting.city.as_ref().ok_or_else(|| {
+        WeatherError::ConfigMissing(
+            "Failed to read user setting! Please run 'set-location' command to configure settings."
+                .to_string(),
+        )
+    })?;
+
+    let now = clock.now();
+    let city_offset = cached_utc_offset(&setting);
+    let offset = city_offset.unwrap_or_else(|| now.with_timezone(&Local).offset().fix());
+    let date = date.unwrap_or_else(|| now.with_timezone(&offset).date_naive());
+
+    let day = SunDay::new(date, city.lat, city.lon);
+    let previous_day = SunDay::new(date - Duration::days(1), city.lat, city.lon);
+    let day_length_change = day.day_length() - previous_day.day_length();
+    let position = sun_position(now, city.lat, city.lon);
+    let polar = day.polar_day.map(|polar_day| match polar_day {
+        true => "day",
+        false => "night",
+    });
+
+    if output == OutputFormat::Json {
+        let time =
+            |time: Option<DateTime<Utc>>| time.map(|time| time.with_timezone(&offset).to_rfc3339());
+        let windows = |windows: &[(DateTime<Utc>, DateTime<Utc>)]| {
+            windows
+                .iter()
+                .map(|(start, end)| {
+                    serde_json::json!({ "start": time(Some(*start)), "end": time(Some(*end)) })
+                })
+                .collect::<Vec<serde_json::Value>>()
+        };
+        let output = serde_json::json!({
+            "city": { "name": city.name, "country": city.country, "lat": city.lat, "lon": city.lon },
+            "date": date.to_string(),
+            "utc_offset": offset.to_string(),
+            "astronomical_dawn": time(day.astronomical.dawn),
+            "nautical_dawn": time(day.nautical.dawn),
+            "civil_dawn": time(day.civil.dawn),
+            "sunrise": time(day.sun.dawn),
+            "solar_noon": time(Some(day.solar_noon)),
+            "sunset": time(day.sun.dusk),
+            "civil_dusk": time(day.civil.dusk),
+            "nautical_dusk": time(day.nautical.dusk),
+            "astronomical_dusk": time(day.astronomical.dusk),
+            "golden_hours": windows(&day.golden_hours),
+            "blue_hours": windows(&day.blue_hours),
+            "day_length": day.day_length().num_seconds(),
+            "day_length_change": day_length_change.num_seconds(),
+            "polar": polar,
+            "elevation": (position.elevation * 100.0).round() / 100.0,
+            "azimuth": (position.azimuth * 100.0).round() / 100.0,
+        });
+
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let time_formatter = TimeFormatter::new(&setting.time);
+    let time = |time: Option<DateTime<Utc>>| match time {
+        Some(time) => time_formatter.time(&time.with_timezone(&offset)),
+        None => "-".to_string(),
+    };
+    let windows = |windows: &[(DateTime<Utc>, DateTime<Utc>)]| match windows.is_empty() {
+        true => "-".to_string(),
+        false => windows
+            .iter()
+            .map(|(start, end)| format!("{} - {}", time(Some(*start)), time(Some(*end))))
+            .collect::<Vec<String>>()
+            .join(", "),
+    };
+    let hours_and_minutes = |duration: Duration| {
+        format!(
+            "{}h {}m",
+            duration.num_minutes() / 60,
+            duration.num_minutes() % 60
+        )
+    };
+
+    println!(
+        "\n{} ({}) / {} (UTC{})",
+        city.name, city.country, date, offset
+    );
+    if city_offset.is_none() {
+        println!("* Times are in your time zone. Run \"weather-cli check\" to use the city's.");
+    }
+    match polar {
+        Some("day") => println!("- Polar day: the sun doesn't set."),
+        Some(_) => println!("- Polar night: the sun doesn't rise."),
+        None => {}
+    }
+    println!("\n- Astronomical dawn: {}", time(day.astronomical.dawn));
+    println!("- Nautical dawn: {}", time(day.nautical.dawn));
+    println!("- Civil dawn: {}", time(day.civil.dawn));
+    println!("- Sunrise: {}", time(day.sun.dawn));
+    println!("- Solar noon: {}", time(Some(day.solar_noon)));
+    println!("- Sunset: {}", time(day.sun.dusk));
+    println!("- Civil dusk: {}", time(day.civil.dusk));
+    println!("- Nautical dusk: {}", time(day.nautical.dusk));
+    println!("- Astronomical dusk: {}", time(day.astronomical.dusk));
+    println!("\n- Golden hour: {}", windows(&day.golden_hours));
+    println!("- Blue hour: {}", windows(&day.blue_hours));
+    println!(
+        "- Day length: {} ({} from yesterday)",
+        hours_and_minutes(day.day_length()),
+        format_change(day_length_change)
+    );
+    println!(
+        "- Sun now: elevation {:.1}°, azimuth {:.1}° ({})",
+        position.elevation,
+        position.azimuth,
+        compass_direction(position.azimuth)
+    );
+
+    Ok(())
+}
@@ -22,6 +22,7 @@ mod unit_test {
                 country: String::from("GB"),
             }),
             units: Some(Units::Imperial),
+            ..Default::default()
         };
 
         println!("{:#?}", option_setting_args);
@@ -36,4 +37,999 @@ mod unit_test {
         assert_eq!(json_data.city.unwrap().name, String::from("London"));
         assert_eq!(json_data.units.unwrap(), Units::Imperial);
     }
+
+    #[test]
+    fn test_usage_count_since() {
+        use crate::types::usage_types::{RequestRecord, UsageLedger};
+
+        let record = |timestamp: i64| RequestRecord {
+            endpoint: String::from("weather"),
+            timestamp,
+            status: Some(200),
+        };
+        let ledger = UsageLedger {
+            requests: vec![record(100), record(160), record(200), record(260)],
+        };
+
+        assert_eq!(ledger.count_since(0), 4);
+        assert_eq!(ledger.count_since(200), 2);
+        assert_eq!(ledger.count_since(261), 0);
+    }
+
+    #[test]
+    fn test_error_exit_code() {
+        use anyhow::{anyhow, Context};
+
+        use crate::error::{exit_code, WeatherError};
+
+        let error = anyhow::Error::from(WeatherError::RateLimited {
+            message: String::from("limited"),
+            status: Some(429),
+        });
+        assert_eq!(exit_code(&error), 6);
+
+        let error = Err::<(), _>(WeatherError::InvalidApiKey)
+            .context("Failed to check the weather.")
+            .unwrap_err();
+        assert_eq!(exit_code(&error), 4);
+
+        assert_eq!(exit_code(&anyhow!("Unknown error.")), 1);
+    }
+
+    #[test]
+    fn test_api_error_from_response() {
+        use serde_json::json;
+
+        use crate::{api_error_from_response, error::WeatherError};
+
+        let error =
+            api_error_from_response(401, &json!({ "cod": 401, "message": "Invalid API key." }));
+        assert!(matches!(error, Some(WeatherError::InvalidApiKey)));
+
+        let error = api_error_from_response(429, &json!({ "cod": 429 }));
+        assert!(matches!(
+            error,
+            Some(WeatherError::RateLimited {
+                status: Some(429),
+                ..
+            })
+        ));
+
+        let error = api_error_from_response(502, &serde_json::Value::Null);
+        assert!(matches!(
+            error,
+            Some(WeatherError::Network {
+                status: Some(502),
+                ..
+            })
+        ));
+
+        let error =
+            api_error_from_response(200, &json!({ "cod": "400", "message": "wrong latitude" }));
+        assert_eq!(
+            error.unwrap().to_string(),
+            "OpenWeather rejected the request (400): wrong latitude"
+        );
+
+        // Geocoding responses are arrays without an envelope.
+        assert!(api_error_from_response(200, &json!([])).is_none());
+    }
+
+    #[test]
+    fn test_resolve_api_key_precedence() {
+        use crate::{
+            api_key::{resolve_api_key, resolve_api_keys, ApiKeySource},
+            types::user_settings::{ApiSetting, NamedApiKey},
+        };
+
+        let mut api_setting = ApiSetting {
+            key: Some(String::from("SETTING_KEY")),
+            ..Default::default()
+        };
+        let resolved = resolve_api_key(&api_setting, None).unwrap();
+        assert_eq!(resolved.key, "SETTING_KEY");
+        assert_eq!(resolved.source, ApiKeySource::Setting);
+
+        api_setting.key_command = Some(String::from("echo COMMAND_KEY"));
+        let resolved = resolve_api_key(&api_setting, None).unwrap();
+        assert_eq!(resolved.key, "COMMAND_KEY");
+        assert!(matches!(resolved.source, ApiKeySource::Command(_)));
+
+        let resolved = resolve_api_key(&api_setting, Some("ENVIRONMENT_KEY")).unwrap();
+        assert_eq!(resolved.key, "ENVIRONMENT_KEY");
+        assert_eq!(resolved.source, ApiKeySource::Environment);
+
+        assert!(resolve_api_key(&ApiSetting::default(), None).is_err());
+
+        // A failing primary key falls back to the named keys.
+        api_setting.key_command = Some(String::from("exit 1"));
+        assert!(resolve_api_keys(&api_setting, None).is_err());
+
+        api_setting.keys = vec![NamedApiKey {
+            name: String::from("backup"),
+            key: String::from("BACKUP_KEY"),
+        }];
+        let resolved = resolve_api_keys(&api_setting, None).unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(
+            resolved[0].source,
+            ApiKeySource::Named(String::from("backup"))
+        );
+    }
+
+    #[test]
+    fn test_api_key_encryption_round_trip() {
+        use crate::key_encryption::{decrypt_api_key, encrypt_api_key};
+
+        let api_key = "0123456789abcdef0123456789abcdef";
+        let encrypted = encrypt_api_key(api_key, "correct horse").unwrap();
+
+        assert!(!encrypted.ciphertext.contains(api_key));
+        assert_eq!(
+            decrypt_api_key(&encrypted, "correct horse").unwrap(),
+            api_key
+        );
+
+        // Salt and nonce are random for every encryption.
+        let encrypted_again = encrypt_api_key(api_key, "correct horse").unwrap();
+        assert_ne!(encrypted.ciphertext, encrypted_again.ciphertext);
+    }
+
+    #[test]
+    fn test_api_key_decryption_wrong_passphrase() {
+        use crate::key_encryption::{decrypt_api_key, encrypt_api_key};
+
+        let encrypted =
+            encrypt_api_key("0123456789abcdef0123456789abcdef", "correct horse").unwrap();
+        let error = decrypt_api_key(&encrypted, "battery staple").unwrap_err();
+
+        assert!(error.to_string().contains("passphrase may be wrong"));
+    }
+
+    #[test]
+    fn test_redact_api_key_from_errors() {
+        use crate::{
+            error::{error_to_json, WeatherError},
+            redaction::{redact, register_secret, unregister_secret},
+        };
+
+        let api_key = "fedcba9876543210fedcba9876543210";
+
+        // Connection errors of reqwest include the request URL.
+        let url = crate::make_weather_api_url(0.0, 0.0, api_key, "metric");
+        let request_error = format!(
+            "error sending request for url ({}): error trying to connect: tcp connect error",
+            url
+        );
+        assert!(request_error.contains(api_key));
+
+        let error = anyhow::Error::from(WeatherError::Network {
+            message: redact(&request_error),
+            status: None,
+        });
+        assert!(!format!("{:#}", error).contains(api_key));
+        assert!(!error_to_json(&error).to_string().contains(api_key));
+
+        // Registered keys are redacted outside of URLs too.
+        register_secret(api_key);
+        let error = anyhow::anyhow!("The key {} was rejected.", api_key);
+        let redacted = redact(&error.to_string());
+        let json = error_to_json(&error).to_string();
+        unregister_secret(api_key);
+
+        assert_eq!(redacted, "The key *** was rejected.");
+        assert!(!json.contains(api_key));
+    }
+
+    #[test]
+    fn test_order_api_keys_with_cooldowns() {
+        use crate::{
+            api_key::{order_api_keys, resolve_api_keys},
+            types::{
+                usage_types::KeyCooldowns,
+                user_settings::{ApiSetting, NamedApiKey},
+            },
+        };
+
+        let api_setting = ApiSetting {
+            key: Some(String::from("PRIMARY_KEY")),
+            keys: vec![
+                NamedApiKey {
+                    name: String::from("team-a"),
+                    key: String::from("TEAM_A_KEY"),
+                },
+                NamedApiKey {
+                    name: String::from("team-b"),
+                    key: String::from("TEAM_B_KEY"),
+                },
+            ],
+            ..Default::default()
+        };
+        let names = |cooldowns: &KeyCooldowns, now: i64| -> Vec<String> {
+            order_api_keys(
+                resolve_api_keys(&api_setting, None).unwrap(),
+                cooldowns,
+                now,
+            )
+            .iter()
+            .map(|key| key.name().to_string())
+            .collect()
+        };
+
+        let mut cooldowns = KeyCooldowns::default();
+        assert_eq!(names(&cooldowns, 1000), ["primary", "team-a", "team-b"]);
+
+        cooldowns.until.insert(String::from("primary"), 1060);
+        assert_eq!(names(&cooldowns, 1000), ["team-a", "team-b"]);
+        assert_eq!(names(&cooldowns, 1060), ["primary", "team-a", "team-b"]);
+
+        // Only the key that recovers first is tried when every key is cooling down.
+        cooldowns.until.insert(String::from("team-a"), 1030);
+        cooldowns.until.insert(String::from("team-b"), 4600);
+        assert_eq!(names(&cooldowns, 1000), ["team-a"]);
+    }
+
+    #[test]
+    fn test_unit_system_conversions() {
+        use crate::{
+            types::user_settings::{DisplayUnits, Units},
+            units::{temperature_to_kelvin, wind_speed_to_mps, UnitSystem, WindUnit},
+        };
+
+        let display_units = DisplayUnits {
+            wind: Some(WindUnit::Beaufort),
+            ..Default::default()
+        };
+        let unit_system = UnitSystem::new(&Units::Imperial, &display_units);
+        assert_eq!(unit_system.wind, WindUnit::Beaufort);
+        assert_eq!(unit_system.temperature.symbol(), "°F");
+
+        // Imperial responses are converted back to the same values.
+        let kelvin = temperature_to_kelvin(-40.0, &Units::Imperial);
+        assert!((unit_system.temperature.from_kelvin(kelvin) + 40.0).abs() < 1e-9);
+        let mps = wind_speed_to_mps(10.0, &Units::Imperial);
+        assert!((WindUnit::MilesPerHour.from_mps(mps) - 10.0).abs() < 1e-9);
+
+        // Beaufort scale boundaries.
+        assert_eq!(WindUnit::Beaufort.from_mps(0.0), 0.0);
+        assert_eq!(WindUnit::Beaufort.from_mps(0.5), 1.0);
+        assert_eq!(WindUnit::Beaufort.from_mps(17.2), 8.0);
+        assert_eq!(WindUnit::Beaufort.from_mps(40.0), 12.0);
+    }
+
+    #[test]
+    fn test_meteo_reference_values() {
+        use crate::meteo::{apparent_temperature, dew_point, heat_index, humidex, wind_chill};
+
+        let celsius = |kelvin: f64| kelvin - 273.15;
+        let fahrenheit = |kelvin: f64| celsius(kelvin) * 9.0 / 5.0 + 32.0;
+        let from_celsius = |celsius: f64| celsius + 273.15;
+        let from_fahrenheit = |fahrenheit: f64| (fahrenheit - 32.0) * 5.0 / 9.0 + 273.15;
+        let mph = |mph: f64| mph * 0.44704;
+        let kmh = |kmh: f64| kmh / 3.6;
+
+        // Dew point (°C) by temperature (°C) and relative humidity.
+        for (temperature, humidity, expected) in
+            [(20.0, 50.0, 9.3), (30.0, 70.0, 23.9), (0.0, 80.0, -3.0)]
+        {
+            let value = celsius(dew_point(from_celsius(temperature), humidity));
+            assert!((value - expected).abs() < 0.1, "dew point {}", value);
+        }
+
+        // NWS heat index table. (°F)
+        for (temperature, humidity, expected) in [
+            (80.0, 40.0, 80.0),
+            (90.0, 70.0, 106.0),
+            (100.0, 50.0, 118.0),
+            (86.0, 90.0, 105.0),
+        ] {
+            let value = fahrenheit(heat_index(from_fahrenheit(temperature), humidity).unwrap());
+            assert_eq!(value.round(), expected, "heat index {}", value);
+        }
+        assert!(heat_index(from_fahrenheit(75.0), 50.0).is_none());
+        assert!(heat_index(from_fahrenheit(79.9), 50.0).is_none());
+        assert!(heat_index(from_fahrenheit(80.0), 50.0).is_some());
+        // At 81 °F and 20 %, the simple formula gives 79.74 °F, below 80 °F,
+        // so it's used instead of the Rothfusz regression. (79.21 °F)
+        let value = fahrenheit(heat_index(from_fahrenheit(81.0), 20.0).unwrap());
+        assert!((value - 79.74).abs() < 0.01, "heat index {}", value);
+
+        // NWS wind chill chart. (°F, mph)
+        for (temperature, wind, expected) in
+            [(0.0, 15.0, -19.0), (30.0, 10.0, 21.0), (-20.0, 30.0, -53.0)]
+        {
+            let value = fahrenheit(wind_chill(from_fahrenheit(temperature), mph(wind)).unwrap());
+            assert_eq!(value.round(), expected, "wind chill {}", value);
+        }
+        // Environment Canada wind chill index. (°C, km/h)
+        let value = celsius(wind_chill(from_celsius(-20.0), kmh(30.0)).unwrap());
+        assert_eq!(value.round(), -33.0);
+        assert!(wind_chill(from_celsius(15.0), kmh(30.0)).is_none());
+        assert!(wind_chill(from_celsius(10.1), kmh(30.0)).is_none());
+        assert!(wind_chill(from_celsius(10.0), kmh(30.0)).is_some());
+        assert!(wind_chill(from_celsius(-5.0), kmh(2.0)).is_none());
+        assert!(wind_chill(from_celsius(-5.0), kmh(4.7)).is_none());
+        assert!(wind_chill(from_celsius(-5.0), kmh(4.8)).is_some());
+
+        // Environment Canada humidex table. (°C, dew point °C)
+        for (temperature, dew_point, expected) in
+            [(30.0, 20.0, 38.0), (25.0, 15.0, 29.0), (35.0, 25.0, 47.0)]
+        {
+            let value = celsius(humidex(from_celsius(temperature), from_celsius(dew_point)));
+            assert_eq!(value.round(), expected, "humidex {}", value);
+        }
+
+        // Australian apparent temperature. (°C, %, m/s)
+        // Expected values use the saturation vapour pressures of the CRC Handbook
+        // (10 °C 12.28 hPa, 25 °C 31.70 hPa, 30 °C 42.47 hPa, 35 °C 56.29 hPa)
+        // instead of the approximation in `apparent_temperature`.
+        for (temperature, humidity, wind, expected) in [
+            (25.0, 50.0, 0.0, 26.2),
+            (30.0, 60.0, 5.0, 30.9),
+            (10.0, 80.0, 10.0, 2.2),
+            (35.0, 40.0, 2.0, 37.0),
+        ] {
+            let value = celsius(apparent_temperature(
+                from_celsius(temperature),
+                humidity,
+                wind,
+            ));
+            assert!(
+                (value - expected).abs() < 0.1,
+                "apparent temperature {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_condition_icons_day_and_night() {
+        use crate::{
+            icons::{ascii_art, condition_icon, is_night, IconStyle},
+            types::response_types::Weather,
+        };
+
+        let weather = |id: u32, icon: &str| -> Weather {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "main": "", "description": "", "icon": icon,
+            }))
+            .unwrap()
+        };
+
+        let clear_day = weather(800, "01d");
+        let clear_night = weather(800, "01n");
+        assert!(!is_night(&clear_day));
+        assert!(is_night(&clear_night));
+        assert_eq!(condition_icon(&clear_day, IconStyle::Emoji), Some("☀️"));
+        assert_eq!(condition_icon(&clear_night, IconStyle::Emoji), Some("🌙"));
+        assert_ne!(ascii_art(&clear_day), ascii_art(&clear_night));
+        assert_eq!(condition_icon(&clear_day, IconStyle::Ascii), None);
+
+        // Rain looks the same by day and night.
+        assert_eq!(
+            condition_icon(&weather(500, "10d"), IconStyle::NerdFont),
+            condition_icon(&weather(500, "10n"), IconStyle::NerdFont)
+        );
+        assert!(ascii_art(&weather(500, "10d"))
+            .iter()
+            .all(|line| line.chars().count() == 13));
+    }
+
+    #[test]
+    fn test_theme_colors() {
+        use crate::{
+            theme::{Painter, Theme, ThemeName},
+            types::response_types::ConditionCategory,
+        };
+
+        // Unset colors of a theme file follow the default theme.
+        let theme: Theme = serde_json::from_str(r##"{ "hot": "#ff0000" }"##).unwrap();
+        assert_eq!(theme.hot, "#ff0000");
+        assert_eq!(theme.rain, Theme::default().rain);
+
+        let painter = Painter {
+            theme,
+            enabled: true,
+        };
+        assert_eq!(
+            painter.temperature("35°C", 308.15),
+            "\x1b[38;2;255;0;0m35°C\x1b[0m"
+        );
+        assert_eq!(painter.temperature("-5°C", 268.15), "\x1b[34m-5°C\x1b[0m");
+        assert_eq!(painter.condition("Fog", None), "Fog");
+        assert_eq!(
+            painter.condition("Rain", Some(ConditionCategory::Rain)),
+            "\x1b[34mRain\x1b[0m"
+        );
+
+        let monochrome = Painter {
+            theme: Theme::builtin(ThemeName::Monochrome),
+            enabled: true,
+        };
+        assert_eq!(monochrome.temperature("15°C", 288.15), "15°C");
+
+        let disabled = Painter {
+            theme: Theme::default(),
+            enabled: false,
+        };
+        assert_eq!(disabled.warning("ERROR"), "ERROR");
+    }
+
+    #[test]
+    fn test_time_formatter() {
+        use chrono::{FixedOffset, TimeZone, Utc};
+
+        use crate::time_display::{validate_clock_format, TimeFormatter, TimeZoneDisplay};
+
+        let city = FixedOffset::west_opt(4 * 3600).unwrap();
+        let sunset = city.with_ymd_and_hms(2024, 10, 18, 18, 24, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 10, 18, 20, 10, 0).unwrap();
+
+        let mut formatter = TimeFormatter {
+            pattern: "%H:%M",
+            zone: TimeZoneDisplay::City,
+            relative: false,
+        };
+        assert_eq!(
+            formatter.event("Sunset", &sunset, Some(now)),
+            "Sunset: 18:24"
+        );
+
+        formatter.relative = true;
+        assert_eq!(
+            formatter.event("Sunset", &sunset, Some(now)),
+            "Sunset in 2h 14m (18:24)"
+        );
+        assert_eq!(formatter.event("Sunset", &sunset, None), "Sunset: 18:24");
+        assert_eq!(
+            formatter.event("Sunset", &sunset, Some(now + chrono::Duration::hours(5))),
+            "Sunset 2h 46m ago (18:24)"
+        );
+
+        assert!(validate_clock_format("24h").is_ok());
+        assert!(validate_clock_format("%a %H:%M").is_ok());
+        assert!(validate_clock_format("%Q").is_err());
+    }
+
+    #[test]
+    fn test_upcoming_sun_events() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        use crate::{
+            clock::FixedClock,
+            daylight::{upcoming_sun_events, Daylight, EventInfo},
+        };
+
+        let assert_near = |time: DateTime<Utc>, expected: DateTime<Utc>| {
+            assert!(
+                (time - expected).num_seconds().abs() <= 120,
+                "{} isn't near {}",
+                time,
+                expected
+            );
+        };
+
+        // Toronto, 2024-10-18: sunrise 07:36, sunset 18:28 (UTC-4)
+        // 2024-10-19: sunrise 07:37, sunset 18:27
+        let sunrise = Utc.with_ymd_and_hms(2024, 10, 18, 11, 36, 0).unwrap();
+        let sunset = Utc.with_ymd_and_hms(2024, 10, 18, 22, 28, 0).unwrap();
+        let next_sunrise = Utc.with_ymd_and_hms(2024, 10, 19, 11, 37, 0).unwrap();
+        let next_sunset = Utc.with_ymd_and_hms(2024, 10, 19, 22, 27, 0).unwrap();
+        let upcoming = |time: DateTime<Utc>| {
+            let daylight = upcoming_sun_events(
+                sunrise.timestamp(),
+                sunset.timestamp(),
+                -4 * 3600,
+                43.6535,
+                -79.3839,
+                &FixedClock(time),
+            )
+            .unwrap();
+            match daylight {
+                Daylight::Events(EventInfo::Sunrise(first), Some(EventInfo::Sunset(second))) => {
+                    ("sunrise", first.to_utc(), second.to_utc())
+                }
+                Daylight::Events(EventInfo::Sunset(first), Some(EventInfo::Sunrise(second))) => {
+                    ("sunset", first.to_utc(), second.to_utc())
+                }
+                _ => panic!("expected sunrise and sunset"),
+            }
+        };
+        let at_hour = |hour: u32| Utc.with_ymd_and_hms(2024, 10, 18, hour, 0, 0).unwrap();
+
+        // Before sunrise, during the day, and after sunset.
+        assert_eq!(upcoming(at_hour(9)), ("sunrise", sunrise, sunset));
+
+        let (first, event, following) = upcoming(at_hour(15));
+        assert_eq!((first, event), ("sunset", sunset));
+        assert_near(following, next_sunrise);
+
+        let (first, event, following) = upcoming(at_hour(23));
+        assert_eq!(first, "sunrise");
+        assert_near(event, next_sunrise);
+        assert_near(following, next_sunset);
+
+        // Data cached a few days ago still gives the next events.
+        // 2024-10-22: sunrise 07:41 (UTC-4)
+        let (first, event, _) = upcoming(Utc.with_ymd_and_hms(2024, 10, 22, 1, 0, 0).unwrap());
+        assert_eq!(first, "sunrise");
+        assert_near(
+            event,
+            Utc.with_ymd_and_hms(2024, 10, 22, 11, 41, 0).unwrap(),
+        );
+
+        // Tromsø around the start of polar day. With refraction, the sun sets
+        // for the last time at 00:12 on 2024-05-17, and rises again at 01:08. (UTC+2)
+        let tromso = |time: DateTime<Utc>| {
+            upcoming_sun_events(0, 0, 7200, 69.6492, 18.9553, &FixedClock(time)).unwrap()
+        };
+        let may = |day: u32, hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(2024, 5, day, hour, minute, 0).unwrap()
+        };
+        assert!(matches!(
+            tromso(may(16, 12, 0)),
+            Daylight::Events(EventInfo::Sunset(sunset), Some(EventInfo::Sunrise(sunrise)))
+                if (sunset.to_utc() - may(16, 22, 12)).num_minutes().abs() <= 2
+                    && (sunrise.to_utc() - may(16, 23, 8)).num_minutes().abs() <= 2
+        ));
+        assert!(matches!(
+            tromso(may(16, 22, 30)),
+            Daylight::Events(EventInfo::Sunrise(sunrise), None)
+                if (sunrise.to_utc() - may(16, 23, 8)).num_minutes().abs() <= 2
+        ));
+        for day in [17, 19, 20, 21] {
+            assert!(matches!(tromso(may(day, 12, 0)), Daylight::PolarDay));
+        }
+
+        // Tromsø in December, the Antarctic in June, and the equator.
+        let june = FixedClock(Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap());
+        let december = FixedClock(Utc.with_ymd_and_hms(2024, 12, 21, 12, 0, 0).unwrap());
+        assert!(matches!(
+            upcoming_sun_events(1734782400, 1734782400, 3600, 69.6, 18.9, &december),
+            Ok(Daylight::PolarNight)
+        ));
+        assert!(matches!(
+            upcoming_sun_events(0, 0, 0, -75.1, 123.3, &june),
+            Ok(Daylight::PolarNight)
+        ));
+        assert!(matches!(
+            upcoming_sun_events(0, 0, 0, 0.0, 0.0, &june),
+            Ok(Daylight::Events(_, Some(_)))
+        ));
+    }
+
+    #[test]
+    fn test_noaa_sun_events() {
+        use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+        use crate::sun::{sun_position, SunDay};
+
+        let assert_near = |time: Option<DateTime<Utc>>, expected: DateTime<Utc>| {
+            let time = time.expect("expected a time");
+            assert!(
+                (time - expected).num_seconds().abs() <= 120,
+                "{} != {}",
+                time,
+                expected
+            );
+        };
+        let utc = |month: u32, day: u32, hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0)
+                .unwrap()
+        };
+
+        // London on the June solstice. (NOAA Solar Calculator)
+        let (latitude, longitude) = (51.5074, -0.1278);
+        let london = SunDay::new(
+            NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+            latitude,
+            longitude,
+        );
+        assert_near(london.sun.dawn, utc(6, 21, 3, 43));
+        assert_near(london.sun.dusk, utc(6, 21, 20, 21));
+        assert_near(Some(london.solar_noon), utc(6, 21, 12, 2));
+        assert!(london.nautical.dawn.is_some());
+        // The sun stays above -18° all night.
+        assert_eq!(london.astronomical.dawn, None);
+        assert_eq!(london.polar_day, None);
+        assert!((london.day_length().num_minutes() - (16 * 60 + 38)).abs() <= 2);
+        assert_eq!(london.golden_hours.len(), 2);
+
+        let noon = sun_position(london.solar_noon, latitude, longitude);
+        assert!((noon.elevation - 61.95).abs() < 0.1, "{}", noon.elevation);
+        assert!((noon.azimuth - 180.0).abs() < 0.5, "{}", noon.azimuth);
+
+        // Tromsø has polar day in June and polar night in December.
+        let (latitude, longitude) = (69.6492, 18.9553);
+        let june = SunDay::new(
+            NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+            latitude,
+            longitude,
+        );
+        let december = SunDay::new(
+            NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(),
+            latitude,
+            longitude,
+        );
+        assert_eq!(june.polar_day, Some(true));
+        assert_eq!(june.day_length().num_hours(), 24);
+        assert_eq!(december.polar_day, Some(false));
+        assert_eq!(december.day_length().num_hours(), 0);
+        // Civil twilight still happens at noon in the polar night.
+        assert!(december.civil.dawn.is_some());
+    }
+
+    #[test]
+    fn test_moon_against_ephemeris() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+
+        use crate::moon::{moon_rise_set, next_lunar_phase, MoonInfo, MoonPhase};
+
+        let utc = |year: i32, month: u32, day: u32, hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+                .unwrap()
+        };
+        let assert_near = |time: DateTime<Utc>, expected: DateTime<Utc>, minutes: i64| {
+            assert!(
+                (time - expected).num_minutes().abs() <= minutes,
+                "{} != {}",
+                time,
+                expected
+            );
+        };
+
+        // New and full moons of 2024. (USNO)
+        assert_near(
+            next_lunar_phase(utc(2024, 1, 1, 0, 0), false),
+            utc(2024, 1, 11, 11, 57),
+            2,
+        );
+        assert_near(
+            next_lunar_phase(utc(2024, 1, 12, 0, 0), true),
+            utc(2024, 1, 25, 17, 54),
+            2,
+        );
+        assert_near(
+            next_lunar_phase(utc(2024, 3, 30, 0, 0), false),
+            utc(2024, 4, 8, 18, 21),
+            2,
+        );
+        assert_near(
+            next_lunar_phase(utc(2024, 9, 1, 0, 0), true),
+            utc(2024, 9, 18, 2, 34),
+            2,
+        );
+
+        // The total solar eclipse and the partial lunar eclipse.
+        // The full moon is 15.03 days after the new moon of 2024-09-03 01:55 UTC.
+        let new_moon = MoonInfo::new(utc(2024, 4, 8, 18, 24));
+        assert_eq!(new_moon.phase, MoonPhase::NewMoon);
+        assert!(new_moon.illumination < 0.01);
+        assert!(new_moon.age < 0.01, "{}", new_moon.age);
+        let full_moon = MoonInfo::new(utc(2024, 9, 18, 2, 34));
+        assert_eq!(full_moon.phase, MoonPhase::FullMoon);
+        assert!(full_moon.illumination > 0.99);
+        assert!((full_moon.age - 15.03).abs() < 0.01, "{}", full_moon.age);
+
+        // First quarter of 2024-01-18 03:52 UTC is half illuminated.
+        let first_quarter = MoonInfo::new(utc(2024, 1, 18, 3, 52));
+        assert_eq!(first_quarter.phase, MoonPhase::FirstQuarter);
+        assert!((first_quarter.illumination - 0.5).abs() < 0.02);
+
+        // Moonrise and moonset in Toronto, within 3 minutes.
+        // These come from the ELP-2000/82 series of Meeus (Astronomical Algorithms,
+        // ch. 47) with the USNO horizon of 0.7275 π - 0.5667°, not from the USNO
+        // tables, which still have to be checked against:
+        // https://aa.usno.navy.mil/data/RS_OneDay
+        // The moon doesn't rise on 2024-10-24: it rises at 23:28 the day before,
+        // and at 00:38 the day after. (UTC-4)
+        const RISE_SET_TOLERANCE: i64 = 3;
+        let (latitude, longitude) = (43.6535, -79.3839);
+        let edt = FixedOffset::west_opt(4 * 3600).unwrap();
+        let rise_set = |month: u32, day: u32| {
+            let date = NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+            moon_rise_set(date, edt, latitude, longitude)
+        };
+
+        let harvest_moon = rise_set(9, 17);
+        assert_near(
+            harvest_moon.rise.unwrap(),
+            utc(2024, 9, 17, 23, 21),
+            RISE_SET_TOLERANCE,
+        );
+        assert_near(
+            harvest_moon.set.unwrap(),
+            utc(2024, 9, 17, 10, 9),
+            RISE_SET_TOLERANCE,
+        );
+
+        let eclipse = rise_set(4, 8);
+        assert_near(
+            eclipse.rise.unwrap(),
+            utc(2024, 4, 8, 10, 42),
+            RISE_SET_TOLERANCE,
+        );
+        assert_near(
+            eclipse.set.unwrap(),
+            utc(2024, 4, 9, 0, 7),
+            RISE_SET_TOLERANCE,
+        );
+
+        let no_moonrise = rise_set(10, 24);
+        assert_eq!(no_moonrise.rise, None);
+        assert_near(
+            no_moonrise.set.unwrap(),
+            utc(2024, 10, 24, 19, 21),
+            RISE_SET_TOLERANCE,
+        );
+        assert_near(
+            rise_set(10, 23).rise.unwrap(),
+            utc(2024, 10, 24, 3, 29),
+            RISE_SET_TOLERANCE,
+        );
+        assert_near(
+            rise_set(10, 25).rise.unwrap(),
+            utc(2024, 10, 25, 4, 38),
+            RISE_SET_TOLERANCE,
+        );
+    }
+
+    #[test]
+    fn test_cache_entry_freshness() {
+        use chrono::Utc;
+
+        use crate::types::cache_types::CacheEntry;
+
+        let entry = |age: i64| CacheEntry {
+            fetched_at: Utc::now().timestamp() - age,
+            body: String::new(),
+        };
+
+        assert!(entry(0).is_fresh(600));
+        assert!(entry(590).is_fresh(600));
+        // An entry as old as the TTL is stale.
+        assert!(!entry(600).is_fresh(600));
+        assert!(!entry(601).is_fresh(600));
+        assert!(!entry(0).is_fresh(0));
+        // Entries from the future (clock changes) count as just fetched.
+        assert_eq!(entry(-60).age(), 0);
+    }
+
+    #[test]
+    fn test_offline_weather_data() {
+        use chrono::Utc;
+
+        use crate::{
+            api_usage::{offline_weather_data, stale_weather_data},
+            error::{error_to_json, exit_code, WeatherError},
+            types::cache_types::CacheEntry,
+        };
+
+        // Without a cached entry, offline mode fails as a network error.
+        let Err(error) = offline_weather_data(None) else {
+            panic!("expected an error");
+        };
+        assert_eq!(exit_code(&error), 5);
+        assert_eq!(error_to_json(&error)["error"]["kind"], "network");
+
+        let body = serde_json::json!({
+            "coord": { "lon": -79.38, "lat": 43.65 },
+            "weather": [{ "id": 701, "main": "Mist", "description": "mist", "icon": "50d" }],
+            "main": { "temp": 11.3, "pressure": 1014, "humidity": 93, "temp_min": 9.9, "temp_max": 13.1 },
+            "wind": { "speed": 3.6 },
+            "clouds": { "all": 100 },
+            "sys": { "sunrise": 1697542980, "sunset": 1697581440 },
+            "timezone": -14400
+        });
+        let entry = CacheEntry {
+            fetched_at: Utc::now().timestamp() - 2 * 3600,
+            body: body.to_string(),
+        };
+        let (data, notice) = offline_weather_data(Some(entry)).unwrap();
+        assert_eq!(data.weather[0].main, "Mist");
+        assert_eq!(notice, "Offline: showing data from 2 hours ago.");
+
+        // After a failed request, only retryable errors fall back to the stale entry.
+        let stale_entry = || CacheEntry {
+            fetched_at: Utc::now().timestamp() - 3600,
+            body: body.to_string(),
+        };
+        let network_error = || WeatherError::Network {
+            message: String::from("connection refused"),
+            status: None,
+        };
+        let (_, notice) = stale_weather_data(network_error().into(), Some(stale_entry())).unwrap();
+        assert_eq!(
+            notice,
+            "Showing cached data from 1 hour ago. (connection refused)"
+        );
+        let budget_error = WeatherError::RateLimited {
+            message: String::from("Daily budget exceeded."),
+            status: None,
+        };
+        assert!(stale_weather_data(budget_error.into(), Some(stale_entry())).is_ok());
+        for error in [
+            WeatherError::InvalidApiKey,
+            WeatherError::NotFound {
+                message: String::from("city not found"),
+                status: Some(404),
+            },
+        ] {
+            let code = error.exit_code();
+            let Err(error) = stale_weather_data(error.into(), Some(stale_entry())) else {
+                panic!("expected an error");
+            };
+            assert_eq!(exit_code(&error), code);
+        }
+        let Err(error) = stale_weather_data(network_error().into(), None) else {
+            panic!("expected an error");
+        };
+        assert_eq!(exit_code(&error), 5);
+
+        // A broken cached body is a parse error.
+        let entry = CacheEntry {
+            fetched_at: Utc::now().timestamp(),
+            body: String::from("{"),
+        };
+        let Err(error) = offline_weather_data(Some(entry)) else {
+            panic!("expected an error");
+        };
+        assert_eq!(exit_code(&error), 8);
+    }
+
+    #[test]
+    fn test_read_existing_json_file() {
+        use crate::{get_json_file_path, read_existing_json_file};
+
+        let json_name = "missing-test";
+        let path = get_json_file_path(json_name).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let result = read_existing_json_file::<serde_json::Value>(json_name).unwrap();
+        assert!(result.is_none());
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[test]
+    fn test_format_conditions() {
+        use crate::{
+            api_usage::format_conditions,
+            icons::IconStyle,
+            theme::{Painter, Theme},
+            types::response_types::Weather,
+        };
+
+        let painter = Painter {
+            theme: Theme::default(),
+            enabled: false,
+        };
+
+        assert_eq!(format_conditions(&[], IconStyle::Emoji, &painter), "");
+
+        let weather: Vec<Weather> = serde_json::from_value(serde_json::json!([
+            { "id": 701, "main": "Mist", "description": "mist", "icon": "50d" },
+            { "id": 500, "main": "Rain", "description": "light rain", "icon": "10d" }
+        ]))
+        .unwrap();
+        assert_eq!(
+            format_conditions(&weather, IconStyle::None, &painter),
+            "Mist (mist), Rain (light rain)"
+        );
+    }
+
+    /// Runs `doctor` in the directory of the test binary. See `test_doctor_leaves_no_files`.
+    #[tokio::test]
+    #[ignore = "run by test_doctor_leaves_no_files from an empty directory"]
+    async fn doctor_in_executable_directory() {
+        let _ = crate::doctor::run_doctor().await;
+    }
+
+    #[test]
+    fn test_doctor_leaves_no_files() {
+        use std::{fs, process::Command};
+
+        // Settings are next to the executable, so a copy of the test binary
+        // runs `doctor` from an empty directory.
+        let executable = std::env::current_exe().unwrap();
+        let directory =
+            std::env::temp_dir().join(format!("weather-cli-doctor-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let copied_executable = directory.join(executable.file_name().unwrap());
+        fs::copy(&executable, &copied_executable).unwrap();
+
+        // The proxy refuses connections, so the checks fail without leaving this machine.
+        let status = Command::new(&copied_executable)
+            .args([
+                "--ignored",
+                "--exact",
+                "testing::unit_test::doctor_in_executable_directory",
+            ])
+            .env("WEATHER_CLI_API_KEY", "0123456789abcdef0123456789abcdef")
+            .env("HTTPS_PROXY", "http://127.0.0.1:1")
+            .env("ALL_PROXY", "http://127.0.0.1:1")
+            .output()
+            .unwrap()
+            .status;
+
+        let files: Vec<_> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(status.success());
+        assert_eq!(files, vec![executable.file_name().unwrap().to_owned()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_json_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::{get_json_file_path, write_json_file, write_private_json_file};
+
+        // An existing readable file is restricted too.
+        let json_name = "private-test";
+        write_json_file(json_name, &serde_json::json!({})).unwrap();
+        write_private_json_file(json_name, &serde_json::json!({ "key": "secret" })).unwrap();
+
+        let path = get_json_file_path(json_name).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode, 0o600);
+        assert_eq!(contents, r#"{"key":"secret"}"#);
+    }
+
+    #[test]
+    fn test_weather_json() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        use crate::{
+            api_usage::weather_json,
+            clock::FixedClock,
+            moon::MoonInfo,
+            types::{
+                response_types::WeatherApiResponse,
+                user_settings::{City, Units, UserSetting},
+            },
+        };
+
+        let setting = UserSetting {
+            city: Some(City {
+                name: String::from("Toronto"),
+                lat: 43.6535,
+                lon: -79.3839,
+                country: String::from("CA"),
+            }),
+            units: Some(Units::Metric),
+            ..Default::default()
+        };
+        // Toronto, 2024-10-18: sunrise 07:36, sunset 18:28 (UTC-4)
+        let response: WeatherApiResponse = serde_json::from_value(serde_json::json!({
+            "coord": { "lon": -79.38, "lat": 43.65 },
+            "weather": [],
+            "main": { "temp": 11.3, "pressure": 1014, "humidity": 93, "temp_min": 9.9, "temp_max": 13.1 },
+            "wind": { "speed": 3.6 },
+            "clouds": { "all": 100 },
+            "sys": { "sunrise": 1729251360, "sunset": 1729290480 },
+            "timezone": -14400
+        }))
+        .unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 10, 18, 15, 0, 0).unwrap();
+        let output = weather_json(&setting, &response, None, &FixedClock(now)).unwrap();
+
+        // Today's sunrise has passed, so tomorrow's is shown. (07:37)
+        assert_eq!(output["sunset"], "2024-10-18T18:28:00-04:00");
+        let sunrise = DateTime::parse_from_rfc3339(output["sunrise"].as_str().unwrap()).unwrap();
+        let expected = Utc.with_ymd_and_hms(2024, 10, 19, 11, 37, 0).unwrap();
+        assert!((sunrise.to_utc() - expected).num_seconds().abs() <= 120);
+        assert_eq!(sunrise.offset().local_minus_utc(), -4 * 3600);
+        assert!(output["polar"].is_null());
+
+        // The moon is computed at the time of the clock.
+        let age = (MoonInfo::new(now).age * 100.0).round() / 100.0;
+        assert_eq!(output["moon"]["age"], age);
+    }
 }
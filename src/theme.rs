@@ -0,0 +1,278 @@
+// ANSI color themes.
+// Colors are written as space-separated styles: color names (`red`, `bright_blue`),
+// `bold`, `dim`, `underline`, 256-color numbers (`208`), hex colors (`#ff8800`) or `none`.
+
+use crate::types::response_types::ConditionCategory;
+
+/// When to color the output.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorChoice {
+    /// Color when the output is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color a stream. `is_terminal` is whether the stream is a terminal.
+    pub fn is_enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        }
+    }
+}
+
+/// Built-in themes, or the user theme file.
+#[derive(
+    serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    HighContrast,
+    Monochrome,
+    /// `weather-cli-theme.json` next to the executable. Unset colors follow `default`.
+    Custom,
+}
+
+impl std::fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            ThemeName::Default => "default",
+            ThemeName::HighContrast => "high-contrast",
+            ThemeName::Monochrome => "monochrome",
+            ThemeName::Custom => "custom",
+        })
+    }
+}
+
+/// Colors of a theme.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    /// City name.
+    pub heading: String,
+    /// Temperatures below 0 °C.
+    pub freezing: String,
+    /// Temperatures from 0 °C to 10 °C.
+    pub cold: String,
+    /// Temperatures from 10 °C to 20 °C.
+    pub mild: String,
+    /// Temperatures from 20 °C to 30 °C.
+    pub warm: String,
+    /// Temperatures from 30 °C.
+    pub hot: String,
+    pub thunderstorm: String,
+    pub drizzle: String,
+    pub rain: String,
+    pub snow: String,
+    pub atmosphere: String,
+    pub clear: String,
+    pub clouds: String,
+    /// Notices and errors.
+    pub warning: String,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::builtin(ThemeName::Default)
+    }
+}
+
+impl Theme {
+    /// Returns a built-in theme. `ThemeName::Custom` returns the default theme.
+    pub fn builtin(name: ThemeName) -> Theme {
+        let theme = |colors: [&str; 14]| Theme {
+            heading: colors[0].to_string(),
+            freezing: colors[1].to_string(),
+            cold: colors[2].to_string(),
+            mild: colors[3].to_string(),
+            warm: colors[4].to_string(),
+            hot: colors[5].to_string(),
+            thunderstorm: colors[6].to_string(),
+            drizzle: colors[7].to_string(),
+            rain: colors[8].to_string(),
+            snow: colors[9].to_string(),
+            atmosphere: colors[10].to_string(),
+            clear: colors[11].to_string(),
+            clouds: colors[12].to_string(),
+            warning: colors[13].to_string(),
+        };
+
+        match name {
+            ThemeName::Default | ThemeName::Custom => theme([
+                "bold",
+                "blue",
+                "cyan",
+                "green",
+                "yellow",
+                "red",
+                "magenta",
+                "cyan",
+                "blue",
+                "white",
+                "bright_black",
+                "yellow",
+                "white",
+                "red",
+            ]),
+            ThemeName::HighContrast => theme([
+                "bold underline",
+                "bold bright_blue",
+                "bold bright_cyan",
+                "bold bright_green",
+                "bold bright_yellow",
+                "bold bright_red",
+                "bold bright_magenta",
+                "bold bright_cyan",
+                "bold bright_blue",
+                "bold bright_white",
+                "bold bright_white",
+                "bold bright_yellow",
+                "bold bright_white",
+                "bold bright_red",
+            ]),
+            ThemeName::Monochrome => theme([
+                "bold", "none", "none", "none", "none", "bold", "bold", "none", "none", "none",
+                "dim", "none", "none", "bold",
+            ]),
+        }
+    }
+
+    /// Reads a theme by its name.
+    pub fn load(name: ThemeName) -> anyhow::Result<Theme> {
+        use crate::{constants::THEME_JSON_NAME, read_json_file};
+
+        match name {
+            ThemeName::Custom => read_json_file::<Theme>(THEME_JSON_NAME),
+            _ => Ok(Theme::builtin(name)),
+        }
+    }
+
+    /// Returns the color of a temperature in kelvin.
+    pub fn temperature(&self, kelvin: f64) -> &str {
+        match kelvin - 273.15 {
+            celsius if celsius < 0.0 => &self.freezing,
+            celsius if celsius < 10.0 => &self.cold,
+            celsius if celsius < 20.0 => &self.mild,
+            celsius if celsius < 30.0 => &self.warm,
+            _ => &self.hot,
+        }
+    }
+
+    /// Returns the color of a condition category.
+    pub fn condition(&self, category: ConditionCategory) -> &str {
+        match category {
+            ConditionCategory::Thunderstorm => &self.thunderstorm,
+            ConditionCategory::Drizzle => &self.drizzle,
+            ConditionCategory::Rain => &self.rain,
+            ConditionCategory::Snow => &self.snow,
+            ConditionCategory::Atmosphere => &self.atmosphere,
+            ConditionCategory::Clear => &self.clear,
+            ConditionCategory::Clouds => &self.clouds,
+        }
+    }
+}
+
+/// Returns the SGR parameters of a color. Unknown styles are ignored.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::theme::sgr_parameters;
+/// assert_eq!(sgr_parameters("bold red"), "1;31");
+/// assert_eq!(sgr_parameters("bright_blue"), "94");
+/// assert_eq!(sgr_parameters("208"), "38;5;208");
+/// assert_eq!(sgr_parameters("#ff8800"), "38;2;255;136;0");
+/// assert_eq!(sgr_parameters("none"), "");
+/// ```
+pub fn sgr_parameters(color: &str) -> String {
+    const NAMES: [&str; 8] = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+
+    color
+        .split_whitespace()
+        .filter_map(|style| {
+            let style = style.to_lowercase();
+            if let Some(index) = NAMES.iter().position(|name| *name == style) {
+                return Some((30 + index).to_string());
+            }
+            if let Some(index) = style
+                .strip_prefix("bright_")
+                .and_then(|name| NAMES.iter().position(|known| *known == name))
+            {
+                return Some((90 + index).to_string());
+            }
+            if let Some(hex) = style.strip_prefix('#').filter(|hex| hex.len() == 6) {
+                let channel = |range| u8::from_str_radix(&hex[range], 16).ok();
+                let (r, g, b) = (channel(0..2)?, channel(2..4)?, channel(4..6)?);
+                return Some(format!("38;2;{};{};{}", r, g, b));
+            }
+            if let Ok(number) = style.parse::<u8>() {
+                return Some(format!("38;5;{}", number));
+            }
+
+            match style.as_str() {
+                "bold" => Some("1".to_string()),
+                "dim" => Some("2".to_string()),
+                "underline" => Some("4".to_string()),
+                _ => None,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+/// Colors text with a theme, or leaves it as it is when colors are disabled.
+pub struct Painter {
+    pub theme: Theme,
+    pub enabled: bool,
+}
+
+impl Painter {
+    /// Wraps text with the escape sequences of a color.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::theme::{Painter, Theme};
+    /// let painter = Painter { theme: Theme::default(), enabled: true };
+    /// assert_eq!(painter.paint("Hot", "red"), "\x1b[31mHot\x1b[0m");
+    ///
+    /// let painter = Painter { theme: Theme::default(), enabled: false };
+    /// assert_eq!(painter.paint("Hot", "red"), "Hot");
+    /// ```
+    pub fn paint(&self, text: &str, color: &str) -> String {
+        let parameters = sgr_parameters(color);
+        if !self.enabled || parameters.is_empty() {
+            return text.to_string();
+        }
+
+        format!("\x1b[{}m{}\x1b[0m", parameters, text)
+    }
+
+    pub fn heading(&self, text: &str) -> String {
+        self.paint(text, &self.theme.heading)
+    }
+
+    /// Colors a temperature by its value in kelvin.
+    pub fn temperature(&self, text: &str, kelvin: f64) -> String {
+        self.paint(text, self.theme.temperature(kelvin))
+    }
+
+    pub fn condition(&self, text: &str, category: Option<ConditionCategory>) -> String {
+        match category {
+            Some(category) => self.paint(text, self.theme.condition(category)),
+            None => text.to_string(),
+        }
+    }
+
+    pub fn warning(&self, text: &str) -> String {
+        self.paint(text, &self.theme.warning)
+    }
+}
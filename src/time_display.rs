@@ -0,0 +1,186 @@
+// Formatting of sunrise and sunset times.
+// Times can be shown in the city's time zone, the viewer's local time zone, or both.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
+
+use crate::types::user_settings::TimeSetting;
+
+/// Clock format of the `12h` setting value.
+pub const TWELVE_HOUR_FORMAT: &str = "%I:%M %p";
+
+/// Clock format of the `24h` setting value.
+pub const TWENTY_FOUR_HOUR_FORMAT: &str = "%H:%M";
+
+/// Time zone of the displayed times.
+#[derive(
+    serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeZoneDisplay {
+    /// The time zone of the city.
+    #[default]
+    City,
+    /// The time zone of this computer.
+    Local,
+    /// The city's time, followed by the local time when they differ. (ex. `18:24 / 07:24 local`)
+    Both,
+}
+
+impl std::fmt::Display for TimeZoneDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            TimeZoneDisplay::City => "city",
+            TimeZoneDisplay::Local => "local",
+            TimeZoneDisplay::Both => "both",
+        })
+    }
+}
+
+/// Returns the strftime pattern of a `time-format` setting value.
+/// `12h` and `24h` are shorthands, and any other value is used as it is.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::time_display::clock_pattern;
+/// assert_eq!(clock_pattern(None), "%I:%M %p");
+/// assert_eq!(clock_pattern(Some("24h")), "%H:%M");
+/// assert_eq!(clock_pattern(Some("%H:%M:%S")), "%H:%M:%S");
+/// ```
+pub fn clock_pattern(format: Option<&str>) -> &str {
+    match format {
+        None | Some("12h") => TWELVE_HOUR_FORMAT,
+        Some("24h") => TWENTY_FOUR_HOUR_FORMAT,
+        Some(pattern) => pattern,
+    }
+}
+
+/// Checks that a `time-format` setting value is a valid strftime pattern.
+pub fn validate_clock_format(format: &str) -> Result<()> {
+    use chrono::format::{Item, StrftimeItems};
+
+    if StrftimeItems::new(clock_pattern(Some(format))).any(|item| matches!(item, Item::Error)) {
+        return Err(anyhow!(
+            "Invalid time format: {} (use 12h, 24h or a strftime pattern like %H:%M)",
+            format
+        ));
+    }
+
+    Ok(())
+}
+
+/// Formats a duration in days, hours and minutes. (ex. `2h 14m`)
+///
+/// ## Example
+/// ```
+/// # use weather_cli::time_display::format_duration;
+/// # use chrono::Duration;
+/// assert_eq!(format_duration(Duration::minutes(134)), "2h 14m");
+/// assert_eq!(format_duration(Duration::minutes(-45)), "45m");
+/// assert_eq!(format_duration(Duration::hours(26)), "1d 2h");
+/// ```
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes().abs();
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (minutes, "m")]
+        .into_iter()
+        .filter(|(value, _)| *value != 0)
+        .map(|(value, suffix)| format!("{}{}", value, suffix))
+        .collect();
+
+    match parts.is_empty() {
+        true => "0m".to_string(),
+        false => parts.join(" "),
+    }
+}
+
+/// Describes when an event happens relative to `now`. (ex. `in 2h 14m`, `3h ago`)
+pub fn relative_phrase(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let difference = time - now;
+    if difference.num_minutes() == 0 {
+        return "now".to_string();
+    }
+
+    match difference > chrono::Duration::zero() {
+        true => format!("in {}", format_duration(difference)),
+        false => format!("{} ago", format_duration(difference)),
+    }
+}
+
+/// Formats event times with the time settings.
+pub struct TimeFormatter<'a> {
+    pub pattern: &'a str,
+    pub zone: TimeZoneDisplay,
+    pub relative: bool,
+}
+
+impl<'a> TimeFormatter<'a> {
+    /// An invalid `time-format` falls back to the 12-hour format,
+    /// since formatting with it would panic.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::{time_display::TimeFormatter, types::user_settings::TimeSetting};
+    /// let setting = TimeSetting {
+    ///     format: Some(String::from("%Q")),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(TimeFormatter::new(&setting).pattern, "%I:%M %p");
+    /// ```
+    pub fn new(setting: &'a TimeSetting) -> TimeFormatter<'a> {
+        let pattern = match &setting.format {
+            Some(format) if validate_clock_format(format).is_err() => TWELVE_HOUR_FORMAT,
+            format => clock_pattern(format.as_deref()),
+        };
+
+        TimeFormatter {
+            pattern,
+            zone: setting.zone.unwrap_or_default(),
+            relative: setting.relative.unwrap_or(false),
+        }
+    }
+
+    /// Formats a time of the city in the configured time zones.
+    pub fn time<T: TimeZone>(&self, time: &DateTime<T>) -> String
+    where
+        T::Offset: std::fmt::Display,
+    {
+        use chrono::Offset;
+
+        let local = time.with_timezone(&Local);
+        let city_time = time.format(self.pattern).to_string();
+        let local_time = local.format(self.pattern).to_string();
+
+        match self.zone {
+            TimeZoneDisplay::City => city_time,
+            TimeZoneDisplay::Local => format!("{} local", local_time),
+            TimeZoneDisplay::Both => match time.offset().fix() == local.offset().fix() {
+                true => city_time,
+                false => format!("{} / {} local", city_time, local_time),
+            },
+        }
+    }
+
+    /// Formats an event. (ex. `Sunset: 06:24 PM`, or `Sunset in 2h 14m (06:24 PM)`)
+    /// The relative phrase is only added when `now` is given and it is enabled.
+    pub fn event<T: TimeZone>(
+        &self,
+        name: &str,
+        time: &DateTime<T>,
+        now: Option<DateTime<Utc>>,
+    ) -> String
+    where
+        T::Offset: std::fmt::Display,
+    {
+        match now.filter(|_| self.relative) {
+            Some(now) => format!(
+                "{} {} ({})",
+                name,
+                relative_phrase(time.with_timezone(&Utc), now),
+                self.time(time)
+            ),
+            None => format!("{}: {}", name, self.time(time)),
+        }
+    }
+}
@@ -1,2 +1,4 @@
+pub mod cache_types;
 pub mod response_types;
+pub mod usage_types;
 pub mod user_settings;
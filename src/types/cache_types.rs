@@ -0,0 +1,16 @@
+use std::collections::HashMap;
+
+/// Cached API responses, keyed by `cache::CacheKey`.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default)]
+pub struct ResponseCache {
+    #[serde(default)]
+    pub entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct CacheEntry {
+    /// UNIX timestamp of when the response was fetched.
+    pub fetched_at: i64,
+    /// Raw response body.
+    pub body: String,
+}
@@ -40,6 +40,81 @@ pub struct Weather {
     pub icon: Option<String>,
 }
 
+impl Weather {
+    /// Returns the condition category of `id`.
+    pub fn category(&self) -> Option<ConditionCategory> {
+        self.id.and_then(ConditionCategory::from_id)
+    }
+}
+
+/// Weather condition groups of OpenWeather condition codes.
+/// (https://openweathermap.org/weather-conditions)
+///
+/// | code    | category     |
+/// | ------- | ------------ |
+/// | 2xx     | Thunderstorm |
+/// | 3xx     | Drizzle      |
+/// | 5xx     | Rain         |
+/// | 6xx     | Snow         |
+/// | 7xx     | Atmosphere   |
+/// | 800     | Clear        |
+/// | 801-804 | Clouds       |
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConditionCategory {
+    Thunderstorm,
+    Drizzle,
+    Rain,
+    Snow,
+    /// Mist, smoke, haze, dust, fog, sand, ash, squalls and tornadoes.
+    Atmosphere,
+    Clear,
+    Clouds,
+}
+
+impl ConditionCategory {
+    /// Returns the category of a condition code.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::types::response_types::ConditionCategory;
+    /// assert_eq!(ConditionCategory::from_id(501), Some(ConditionCategory::Rain));
+    /// assert_eq!(ConditionCategory::from_id(800), Some(ConditionCategory::Clear));
+    /// assert_eq!(ConditionCategory::from_id(804), Some(ConditionCategory::Clouds));
+    /// assert_eq!(ConditionCategory::from_id(900), None);
+    /// ```
+    pub fn from_id(id: u32) -> Option<ConditionCategory> {
+        match id {
+            200..=299 => Some(ConditionCategory::Thunderstorm),
+            300..=399 => Some(ConditionCategory::Drizzle),
+            500..=599 => Some(ConditionCategory::Rain),
+            600..=699 => Some(ConditionCategory::Snow),
+            700..=799 => Some(ConditionCategory::Atmosphere),
+            800 => Some(ConditionCategory::Clear),
+            801..=899 => Some(ConditionCategory::Clouds),
+            _ => None,
+        }
+    }
+
+    /// Returns the lowercase name of the category. (ex. `thunderstorm`)
+    pub fn name(self) -> &'static str {
+        match self {
+            ConditionCategory::Thunderstorm => "thunderstorm",
+            ConditionCategory::Drizzle => "drizzle",
+            ConditionCategory::Rain => "rain",
+            ConditionCategory::Snow => "snow",
+            ConditionCategory::Atmosphere => "atmosphere",
+            ConditionCategory::Clear => "clear",
+            ConditionCategory::Clouds => "clouds",
+        }
+    }
+}
+
+impl std::fmt::Display for ConditionCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 #[derive(serde::Deserialize)]
 pub struct Main {
     pub temp: f64,
@@ -0,0 +1,24 @@
+/// Local record of outbound API requests.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default)]
+pub struct UsageLedger {
+    #[serde(default)]
+    pub requests: Vec<RequestRecord>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct RequestRecord {
+    /// API endpoint name. (ex. `weather`, `geocoding`)
+    pub endpoint: String,
+    /// UNIX timestamp of the request.
+    pub timestamp: i64,
+    /// HTTP status code. It's `None` when no response was received.
+    pub status: Option<u16>,
+}
+
+/// Cool-down periods of API keys.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default)]
+pub struct KeyCooldowns {
+    /// UNIX timestamps until which each key is skipped, by key name.
+    #[serde(default)]
+    pub until: std::collections::HashMap<String, i64>,
+}
@@ -0,0 +1,356 @@
+// Display units and local conversions.
+// Values from the API are normalized to SI first (kelvin, m/s, hPa, mm, m),
+// and converted to the display units when they are printed.
+
+use crate::types::user_settings::{DisplayUnits, Units};
+
+/// Returns a temperature from the API in kelvin.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::{types::user_settings::Units, units::temperature_to_kelvin};
+/// assert_eq!(temperature_to_kelvin(0.0, &Units::Metric), 273.15);
+/// assert_eq!(temperature_to_kelvin(32.0, &Units::Imperial), 273.15);
+/// ```
+pub fn temperature_to_kelvin(value: f64, units: &Units) -> f64 {
+    match units {
+        Units::Standard => value,
+        Units::Metric => value + 273.15,
+        Units::Imperial => (value - 32.0) * 5.0 / 9.0 + 273.15,
+    }
+}
+
+/// Returns a wind speed from the API in m/s.
+pub fn wind_speed_to_mps(value: f64, units: &Units) -> f64 {
+    match units {
+        Units::Standard | Units::Metric => value,
+        Units::Imperial => value * 0.44704,
+    }
+}
+
+/// Returns the 16-point compass direction of a wind direction in degrees.
+///
+/// ## Example
+/// ```
+/// # use weather_cli::units::compass_direction;
+/// assert_eq!(compass_direction(0.0), "N");
+/// assert_eq!(compass_direction(22.5), "NNE");
+/// assert_eq!(compass_direction(350.0), "N");
+/// assert_eq!(compass_direction(250.0), "WSW");
+/// ```
+pub fn compass_direction(degrees: f64) -> &'static str {
+    const DIRECTIONS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+
+    let index = (degrees.rem_euclid(360.0) / 22.5).round() as usize % DIRECTIONS.len();
+    DIRECTIONS[index]
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TemperatureUnit {
+    #[serde(rename = "kelvin")]
+    Kelvin,
+    #[serde(rename = "celsius")]
+    Celsius,
+    #[serde(rename = "fahrenheit")]
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub fn from_kelvin(self, kelvin: f64) -> f64 {
+        match self {
+            TemperatureUnit::Kelvin => kelvin,
+            TemperatureUnit::Celsius => kelvin - 273.15,
+            TemperatureUnit::Fahrenheit => (kelvin - 273.15) * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Kelvin => "K",
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+
+    /// Formats a temperature in kelvin.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::units::TemperatureUnit;
+    /// assert_eq!(TemperatureUnit::Kelvin.format(282.72), "282.7 K");
+    /// assert_eq!(TemperatureUnit::Celsius.format(282.72), "9.6°C");
+    /// assert_eq!(TemperatureUnit::Fahrenheit.format(282.72), "49.2°F");
+    /// ```
+    pub fn format(self, kelvin: f64) -> String {
+        let value = self.from_kelvin(kelvin);
+        match self {
+            TemperatureUnit::Kelvin => format!("{:.1} {}", value, self.symbol()),
+            _ => format!("{:.1}{}", value, self.symbol()),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum WindUnit {
+    #[serde(rename = "ms")]
+    #[value(name = "ms")]
+    MetersPerSecond,
+    #[serde(rename = "kmh")]
+    #[value(name = "kmh")]
+    KilometersPerHour,
+    #[serde(rename = "mph")]
+    #[value(name = "mph")]
+    MilesPerHour,
+    #[serde(rename = "knots")]
+    Knots,
+    #[serde(rename = "beaufort")]
+    Beaufort,
+}
+
+/// Upper wind speed limits of the Beaufort scale numbers 0 to 11, in m/s.
+const BEAUFORT_LIMITS: [f64; 12] = [
+    0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+];
+
+impl WindUnit {
+    pub fn from_mps(self, mps: f64) -> f64 {
+        match self {
+            WindUnit::MetersPerSecond => mps,
+            WindUnit::KilometersPerHour => mps * 3.6,
+            WindUnit::MilesPerHour => mps / 0.44704,
+            WindUnit::Knots => mps * 3600.0 / 1852.0,
+            WindUnit::Beaufort => BEAUFORT_LIMITS
+                .iter()
+                .position(|limit| mps < *limit)
+                .unwrap_or(BEAUFORT_LIMITS.len()) as f64,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            WindUnit::MetersPerSecond => "m/s",
+            WindUnit::KilometersPerHour => "km/h",
+            WindUnit::MilesPerHour => "mph",
+            WindUnit::Knots => "kn",
+            WindUnit::Beaufort => "Bft",
+        }
+    }
+
+    /// Formats a wind speed in m/s.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::units::WindUnit;
+    /// assert_eq!(WindUnit::KilometersPerHour.format(4.59), "16.5 km/h");
+    /// assert_eq!(WindUnit::Knots.format(4.59), "8.9 kn");
+    /// assert_eq!(WindUnit::Beaufort.format(4.59), "3 Bft");
+    /// ```
+    pub fn format(self, mps: f64) -> String {
+        match self {
+            WindUnit::Beaufort => format!("{} {}", self.from_mps(mps), self.symbol()),
+            _ => format!("{:.1} {}", self.from_mps(mps), self.symbol()),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum PressureUnit {
+    #[serde(rename = "hpa")]
+    #[value(name = "hpa")]
+    Hectopascal,
+    #[serde(rename = "inhg")]
+    #[value(name = "inhg")]
+    InchesOfMercury,
+    #[serde(rename = "mmhg")]
+    #[value(name = "mmhg")]
+    MillimetersOfMercury,
+    #[serde(rename = "kpa")]
+    #[value(name = "kpa")]
+    Kilopascal,
+}
+
+impl PressureUnit {
+    pub fn from_hpa(self, hpa: f64) -> f64 {
+        match self {
+            PressureUnit::Hectopascal => hpa,
+            PressureUnit::InchesOfMercury => hpa * 0.029_529_983_071_4,
+            PressureUnit::MillimetersOfMercury => hpa * 0.750_061_683,
+            PressureUnit::Kilopascal => hpa / 10.0,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            PressureUnit::Hectopascal => "hPa",
+            PressureUnit::InchesOfMercury => "inHg",
+            PressureUnit::MillimetersOfMercury => "mmHg",
+            PressureUnit::Kilopascal => "kPa",
+        }
+    }
+
+    /// Formats a pressure in hPa.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::units::PressureUnit;
+    /// assert_eq!(PressureUnit::Hectopascal.format(1013.0), "1013 hPa");
+    /// assert_eq!(PressureUnit::InchesOfMercury.format(1013.0), "29.91 inHg");
+    /// assert_eq!(PressureUnit::MillimetersOfMercury.format(1013.0), "760 mmHg");
+    /// assert_eq!(PressureUnit::Kilopascal.format(1013.0), "101.3 kPa");
+    /// ```
+    pub fn format(self, hpa: f64) -> String {
+        let value = self.from_hpa(hpa);
+        match self {
+            PressureUnit::Hectopascal | PressureUnit::MillimetersOfMercury => {
+                format!("{:.0} {}", value, self.symbol())
+            }
+            PressureUnit::InchesOfMercury => format!("{:.2} {}", value, self.symbol()),
+            PressureUnit::Kilopascal => format!("{:.1} {}", value, self.symbol()),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum PrecipitationUnit {
+    #[serde(rename = "mm")]
+    #[value(name = "mm")]
+    Millimeters,
+    #[serde(rename = "in")]
+    #[value(name = "in")]
+    Inches,
+}
+
+impl PrecipitationUnit {
+    pub fn from_mm(self, mm: f64) -> f64 {
+        match self {
+            PrecipitationUnit::Millimeters => mm,
+            PrecipitationUnit::Inches => mm / 25.4,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            PrecipitationUnit::Millimeters => "mm",
+            PrecipitationUnit::Inches => "in",
+        }
+    }
+
+    /// Formats a precipitation amount in mm.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::units::PrecipitationUnit;
+    /// assert_eq!(PrecipitationUnit::Millimeters.format(3.16), "3.2 mm");
+    /// assert_eq!(PrecipitationUnit::Inches.format(3.16), "0.12 in");
+    /// ```
+    pub fn format(self, mm: f64) -> String {
+        match self {
+            PrecipitationUnit::Millimeters => format!("{:.1} {}", mm, self.symbol()),
+            PrecipitationUnit::Inches => format!("{:.2} {}", self.from_mm(mm), self.symbol()),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum VisibilityUnit {
+    #[serde(rename = "m")]
+    #[value(name = "m")]
+    Meters,
+    #[serde(rename = "km")]
+    #[value(name = "km")]
+    Kilometers,
+    #[serde(rename = "mi")]
+    #[value(name = "mi")]
+    Miles,
+}
+
+impl VisibilityUnit {
+    pub fn from_meters(self, meters: f64) -> f64 {
+        match self {
+            VisibilityUnit::Meters => meters,
+            VisibilityUnit::Kilometers => meters / 1000.0,
+            VisibilityUnit::Miles => meters / 1609.344,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            VisibilityUnit::Meters => "m",
+            VisibilityUnit::Kilometers => "km",
+            VisibilityUnit::Miles => "mi",
+        }
+    }
+
+    /// Formats a visibility in m.
+    ///
+    /// ## Example
+    /// ```
+    /// # use weather_cli::units::VisibilityUnit;
+    /// assert_eq!(VisibilityUnit::Meters.format(10000.0), "10000 m");
+    /// assert_eq!(VisibilityUnit::Kilometers.format(10000.0), "10.0 km");
+    /// assert_eq!(VisibilityUnit::Miles.format(10000.0), "6.2 mi");
+    /// ```
+    pub fn format(self, meters: f64) -> String {
+        match self {
+            VisibilityUnit::Meters => format!("{:.0} {}", meters, self.symbol()),
+            _ => format!("{:.1} {}", self.from_meters(meters), self.symbol()),
+        }
+    }
+}
+
+/// Units every value is displayed in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnitSystem {
+    pub temperature: TemperatureUnit,
+    pub wind: WindUnit,
+    pub pressure: PressureUnit,
+    pub precipitation: PrecipitationUnit,
+    pub visibility: VisibilityUnit,
+}
+
+impl UnitSystem {
+    /// Returns the default display units of the API units.
+    pub fn for_units(units: &Units) -> UnitSystem {
+        match units {
+            Units::Standard => UnitSystem {
+                temperature: TemperatureUnit::Kelvin,
+                wind: WindUnit::MetersPerSecond,
+                pressure: PressureUnit::Hectopascal,
+                precipitation: PrecipitationUnit::Millimeters,
+                visibility: VisibilityUnit::Meters,
+            },
+            Units::Metric => UnitSystem {
+                temperature: TemperatureUnit::Celsius,
+                wind: WindUnit::MetersPerSecond,
+                pressure: PressureUnit::Hectopascal,
+                precipitation: PrecipitationUnit::Millimeters,
+                visibility: VisibilityUnit::Kilometers,
+            },
+            Units::Imperial => UnitSystem {
+                temperature: TemperatureUnit::Fahrenheit,
+                wind: WindUnit::MilesPerHour,
+                pressure: PressureUnit::Hectopascal,
+                precipitation: PrecipitationUnit::Inches,
+                visibility: VisibilityUnit::Miles,
+            },
+        }
+    }
+
+    /// Returns the display units of the API units, with the configured ones applied.
+    pub fn new(units: &Units, display_units: &DisplayUnits) -> UnitSystem {
+        let defaults = UnitSystem::for_units(units);
+
+        UnitSystem {
+            temperature: display_units.temperature.unwrap_or(defaults.temperature),
+            wind: display_units.wind.unwrap_or(defaults.wind),
+            pressure: display_units.pressure.unwrap_or(defaults.pressure),
+            precipitation: display_units
+                .precipitation
+                .unwrap_or(defaults.precipitation),
+            visibility: display_units.visibility.unwrap_or(defaults.visibility),
+        }
+    }
+}
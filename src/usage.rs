@@ -0,0 +1,137 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    constants::{USAGE_JSON_NAME, USAGE_RETENTION_DAYS, USER_SETTING_JSON_NAME},
+    error::WeatherError,
+    read_json_file,
+    types::{
+        usage_types::{RequestRecord, UsageLedger},
+        user_settings::UserSetting,
+    },
+    write_json_file,
+};
+
+impl UsageLedger {
+    /// Counts requests made at or after the given UNIX timestamp.
+    pub fn count_since(&self, timestamp: i64) -> usize {
+        self.requests
+            .iter()
+            .filter(|record| record.timestamp >= timestamp)
+            .count()
+    }
+}
+
+/// Returns the UNIX timestamp of the start of the day (UTC).
+fn start_of_day(time: DateTime<Utc>) -> i64 {
+    time.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|start| start.and_utc().timestamp())
+        .unwrap_or_else(|| time.timestamp())
+}
+
+/// Records an outbound API request.
+pub fn record_request(endpoint: &str, status: Option<u16>) -> Result<()> {
+    let now = Utc::now().timestamp();
+    let mut ledger = read_json_file::<UsageLedger>(USAGE_JSON_NAME).unwrap_or_default();
+
+    ledger
+        .requests
+        .retain(|record| record.timestamp >= now - USAGE_RETENTION_DAYS * 86400);
+    ledger.requests.push(RequestRecord {
+        endpoint: endpoint.to_string(),
+        timestamp: now,
+        status,
+    });
+
+    write_json_file(USAGE_JSON_NAME, &ledger)
+}
+
+/// Returns an error when another request would exceed the configured budget.
+pub fn check_budget() -> Result<()> {
+    let setting = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    if setting.daily_budget.is_none() && setting.minute_budget.is_none() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let ledger = read_json_file::<UsageLedger>(USAGE_JSON_NAME).unwrap_or_default();
+
+    if let Some(budget) = setting.minute_budget {
+        let used = ledger.count_since((now - Duration::minutes(1)).timestamp());
+        if used >= budget as usize {
+            return Err(WeatherError::RateLimited {
+                message: format!(
+                    "The per-minute API budget ({} calls) is used up. Please try again later.",
+                    budget
+                ),
+                status: None,
+            }
+            .into());
+        }
+    }
+    if let Some(budget) = setting.daily_budget {
+        let used = ledger.count_since(start_of_day(now));
+        if used >= budget as usize {
+            return Err(WeatherError::RateLimited {
+                message: format!(
+                    "The daily API budget ({} calls) is used up. Please try again tomorrow (UTC).",
+                    budget
+                ),
+                status: None,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints API usage from the local ledger.
+pub fn print_usage_report() -> Result<()> {
+    let setting = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    let ledger = read_json_file::<UsageLedger>(USAGE_JSON_NAME)?;
+    let now = Utc::now();
+
+    let with_budget = |used: usize, budget: Option<u32>| match budget {
+        Some(budget) => format!("{} / {} calls", used, budget),
+        None => format!("{} calls", used),
+    };
+
+    println!("\n* API usage");
+    println!(
+        "- Last minute: {}",
+        with_budget(
+            ledger.count_since((now - Duration::minutes(1)).timestamp()),
+            setting.minute_budget
+        )
+    );
+    println!(
+        "- Today (UTC): {}",
+        with_budget(ledger.count_since(start_of_day(now)), setting.daily_budget)
+    );
+
+    println!("\n* Last 7 days (UTC)");
+    for days_ago in 0..7 {
+        let day = now - Duration::days(days_ago);
+        let (start, end) = (start_of_day(day), start_of_day(day) + 86400);
+        let records: Vec<&RequestRecord> = ledger
+            .requests
+            .iter()
+            .filter(|record| (start..end).contains(&record.timestamp))
+            .collect();
+        let failed = records
+            .iter()
+            .filter(|record| !matches!(record.status, Some(200..=299)))
+            .count();
+
+        println!(
+            "{}: {} calls ({} failed)",
+            day.format("%Y-%m-%d"),
+            records.len(),
+            failed
+        );
+    }
+
+    Ok(())
+}
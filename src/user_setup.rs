@@ -1,34 +1,261 @@
 use anyhow::{anyhow, Context, Result};
 
-use crate::{constants::API_JSON_NAME, make_json_file_name, types::user_settings::UserSetting};
+use crate::{
+    constants::API_JSON_NAME, http::RequestOptions, make_json_file_name,
+    types::user_settings::UserSetting,
+};
+
+/// Checks whether an API key looks like an OpenWeather API key.
+/// (32 alphanumeric characters)
+///
+/// ## Example
+/// ```
+/// # use weather_cli::user_setup::is_api_key_well_formed;
+/// assert!(is_api_key_well_formed("0123456789abcdef0123456789ABCDEF"));
+/// assert!(!is_api_key_well_formed("EXAMPLE_KEY"));
+/// ```
+pub fn is_api_key_well_formed(api_key: &str) -> bool {
+    use regex::Regex;
+
+    Regex::new(r"^[a-zA-Z0-9]{32}$")
+        .map(|regex| regex.is_match(api_key))
+        .unwrap_or(false)
+}
+
+/// Verifies an API key with a single request.
+/// Returns the classified `WeatherError` of a failed request.
+pub async fn verify_api_key(api_key: &str, options: &RequestOptions<'_>) -> Result<()> {
+    use crate::{http::send_request, make_weather_api_url, read_json_response};
+
+    let url = make_weather_api_url(0.0, 0.0, api_key, "standard");
+    let response = send_request("weather", url, options).await?;
+    read_json_response::<serde_json::Value>(response.status, &response.body, "WeatherApiResponse")?;
+
+    Ok(())
+}
+
+/// Verifies an API key before it's saved, explaining how to skip the verification.
+///
+/// OpenWeather answers `401` both for invalid keys and for keys that
+/// aren't activated yet, so both cases are explained in the error.
+async fn verify_api_key_for_setup(api_key: &str) -> Result<()> {
+    use crate::{constants::USER_SETTING_JSON_NAME, error::WeatherError, read_json_file};
+
+    let network = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?.network;
+    let options = RequestOptions {
+        network: &network,
+        retry_rate_limited: true,
+        track_usage: true,
+    };
+    let Err(e) = verify_api_key(api_key, &options).await else {
+        return Ok(());
+    };
+    match e.downcast_ref::<WeatherError>() {
+        Some(WeatherError::InvalidApiKey) => Err(e.context(
+            "The API key was rejected. If it was created within the last few hours, \
+            it may not be activated yet. Please try again later, \
+            or use '--no-verify' to save it anyway.",
+        )),
+        Some(WeatherError::Network { status: None, .. }) => Err(e.context(
+            "Couldn't verify the API key because the network is unavailable. \
+            Use '--no-verify' to save it without verification.",
+        )),
+        _ => {
+            let message = format!(
+                "Couldn't verify the API key: {} Use '--no-verify' to save it without verification.",
+                e
+            );
+            Err(e.context(message))
+        }
+    }
+}
 
 /// Sets up an API key.
-pub fn setup_api(api_key_input: String) -> Result<()> {
-    use std::{fs::File, io::Write};
+/// The key is verified against the API unless `verify` is `false`.
+///
+/// With `encrypt`, the key is stored encrypted with a passphrase.
+/// Without `api_key_input`, the saved plain text key is encrypted.
+pub async fn setup_api(api_key_input: Option<String>, verify: bool, encrypt: bool) -> Result<()> {
+    use crate::{
+        key_encryption::{encrypt_api_key, read_passphrase},
+        read_json_file,
+        types::user_settings::ApiSetting,
+        write_private_json_file,
+    };
 
-    use regex::Regex;
+    let mut api_setting = read_json_file::<ApiSetting>(API_JSON_NAME).unwrap_or_default();
 
-    use crate::{get_executable_directory, types::user_settings::ApiSetting};
+    let api_key = match (api_key_input, &api_setting.key) {
+        (Some(api_key_input), _) => {
+            crate::redaction::register_secret(&api_key_input);
+            if !is_api_key_well_formed(&api_key_input) {
+                return Err(anyhow!("Please enter a valid key!"));
+            }
 
-    let executable_dir = get_executable_directory()?;
-    let regex = Regex::new(r"^[a-zA-Z0-9]+$")?;
+            if verify {
+                verify_api_key_for_setup(&api_key_input).await?;
+                println!("Your key is verified.");
+            }
 
-    if api_key_input.len() != 32 || !regex.is_match(&api_key_input) {
-        return Err(anyhow!("Please enter a valid key!"));
+            api_key_input
+        }
+        (None, Some(saved_key)) if encrypt => saved_key.clone(),
+        (None, _) => return Err(anyhow!("Please enter your key with '--key'.")),
+    };
+
+    if encrypt {
+        let passphrase = read_passphrase(true)?;
+        api_setting.encrypted_key = Some(encrypt_api_key(&api_key, &passphrase)?);
+        api_setting.key = None;
     } else {
-        let new_api_setting = ApiSetting { key: api_key_input };
+        api_setting.key = Some(api_key);
+        api_setting.encrypted_key = None;
+    }
+    write_private_json_file(API_JSON_NAME, &api_setting)?;
+
+    println!("Successfully updated your key data!");
+    if api_setting.key_command.is_some() || api_setting.key_file.is_some() {
+        println!("NOTE: 'key-command' or 'key-file' takes precedence over this key.");
+    }
 
-        let api_json_string = serde_json::to_string(&new_api_setting)?;
-        File::create(format!(
-            "{}/{}",
-            executable_dir,
-            make_json_file_name(API_JSON_NAME)
-        ))?
-        .write_all(api_json_string.as_bytes())?;
+    Ok(())
+}
+
+/// Adds a named API key used when the other keys are rejected or rate limited.
+/// A key with the same name is replaced.
+pub async fn add_named_api_key(name: &str, api_key: String, verify: bool) -> Result<()> {
+    use crate::{
+        api_key::PRIMARY_KEY_NAME,
+        read_json_file,
+        types::user_settings::{ApiSetting, NamedApiKey},
+        write_private_json_file,
+    };
 
-        println!("Successfully updated your key data!");
+    crate::redaction::register_secret(&api_key);
+    if name.trim().is_empty() || name == PRIMARY_KEY_NAME {
+        return Err(anyhow!(
+            "Please use a name other than '{}'.",
+            PRIMARY_KEY_NAME
+        ));
+    }
+    if !is_api_key_well_formed(&api_key) {
+        return Err(anyhow!("Please enter a valid key!"));
+    }
+    if verify {
+        verify_api_key_for_setup(&api_key).await?;
+        println!("Your key is verified.");
     }
 
+    let mut api_setting = read_json_file::<ApiSetting>(API_JSON_NAME).unwrap_or_default();
+    let named_key = NamedApiKey {
+        name: name.to_string(),
+        key: api_key,
+    };
+    match api_setting.keys.iter_mut().find(|key| key.name == name) {
+        Some(existing) => *existing = named_key,
+        None => api_setting.keys.push(named_key),
+    }
+    write_private_json_file(API_JSON_NAME, &api_setting)?;
+
+    println!("Successfully added the key '{}'!", name);
+
+    Ok(())
+}
+
+/// Removes a named API key.
+pub fn remove_named_api_key(name: &str) -> Result<()> {
+    use crate::{read_json_file, types::user_settings::ApiSetting, write_private_json_file};
+
+    let mut api_setting = read_json_file::<ApiSetting>(API_JSON_NAME)?;
+    let key_count = api_setting.keys.len();
+    api_setting.keys.retain(|key| key.name != name);
+    if api_setting.keys.len() == key_count {
+        return Err(anyhow!("There is no key named '{}'.", name));
+    }
+    write_private_json_file(API_JSON_NAME, &api_setting)?;
+
+    println!("Successfully removed the key '{}'!", name);
+
+    Ok(())
+}
+
+/// Lists API keys in the order they are tried, with their cool-down periods.
+/// The primary key isn't resolved, so no key command runs and no passphrase is asked.
+pub fn list_api_keys() -> Result<()> {
+    use chrono::Utc;
+
+    use crate::{
+        api_key::{
+            describe_primary_key, environment_api_key, mask_api_key, API_KEY_ENV_NAME,
+            PRIMARY_KEY_NAME,
+        },
+        cache::format_age,
+        constants::KEY_COOLDOWN_JSON_NAME,
+        read_json_file,
+        types::{usage_types::KeyCooldowns, user_settings::ApiSetting},
+    };
+
+    let api_setting = read_json_file::<ApiSetting>(API_JSON_NAME).unwrap_or_default();
+    let cooldowns = read_json_file::<KeyCooldowns>(KEY_COOLDOWN_JSON_NAME).unwrap_or_default();
+    let now = Utc::now().timestamp();
+    let cooldown_status = |name: &str| match cooldowns.until.get(name) {
+        Some(until) if *until > now => format!(" (cooling down for {})", format_age(until - now)),
+        _ => String::new(),
+    };
+
+    if environment_api_key().is_some() {
+        println!("{} overrides every key below.", API_KEY_ENV_NAME);
+    }
+
+    let mut position = 0;
+    if let Some(primary_source) = describe_primary_key(&api_setting) {
+        position += 1;
+        println!(
+            "{}. {}: {}{}",
+            position,
+            PRIMARY_KEY_NAME,
+            primary_source,
+            cooldown_status(PRIMARY_KEY_NAME)
+        );
+    }
+    for named_key in &api_setting.keys {
+        position += 1;
+        println!(
+            "{}. {}: {}{}",
+            position,
+            named_key.name,
+            mask_api_key(&named_key.key),
+            cooldown_status(&named_key.name)
+        );
+    }
+    if position == 0 {
+        println!("No API key is configured.");
+    }
+
+    Ok(())
+}
+
+/// Decrypts the saved API key and stores it in plain text again.
+pub fn decrypt_saved_api_key() -> Result<()> {
+    use crate::{
+        key_encryption::{decrypt_api_key, read_passphrase},
+        read_json_file,
+        types::user_settings::ApiSetting,
+        write_private_json_file,
+    };
+
+    let mut api_setting = read_json_file::<ApiSetting>(API_JSON_NAME)?;
+    let encrypted_key = api_setting
+        .encrypted_key
+        .take()
+        .context("There is no encrypted API key.")?;
+
+    let passphrase = read_passphrase(false)?;
+    api_setting.key = Some(decrypt_api_key(&encrypted_key, &passphrase)?);
+    write_private_json_file(API_JSON_NAME, &api_setting)?;
+
+    println!("Your key is stored in plain text again.");
+
     Ok(())
 }
 
@@ -78,3 +305,399 @@ pub fn update_user_settings(setting_args: &UserSetting) -> Result<()> {
 
     Ok(())
 }
+
+/// Removes cached API responses.
+pub fn clear_response_cache() -> Result<()> {
+    crate::cache::clear_cache()?;
+    println!("Successfully cleared the response cache!");
+
+    Ok(())
+}
+
+/// Prints or updates the response cache TTL.
+pub fn setup_cache_ttl(seconds: Option<u64>) -> Result<()> {
+    use crate::{
+        constants::{DEFAULT_CACHE_TTL_SECONDS, USER_SETTING_JSON_NAME},
+        read_json_file, write_json_file,
+    };
+
+    let mut json_data = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+
+    match seconds {
+        Some(seconds) => {
+            json_data.cache_ttl = Some(seconds);
+            write_json_file(USER_SETTING_JSON_NAME, &json_data)?;
+            println!("Cached responses now stay fresh for {} seconds.", seconds);
+        }
+        None => {
+            let seconds = json_data.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL_SECONDS);
+            println!("Cached responses stay fresh for {} seconds.", seconds);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints or updates the API usage budget. `0` removes a limit.
+pub fn setup_usage_budget(daily: Option<u32>, minute: Option<u32>) -> Result<()> {
+    use crate::{constants::USER_SETTING_JSON_NAME, read_json_file, write_json_file};
+
+    let mut json_data = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+
+    if daily.is_some() || minute.is_some() {
+        if let Some(daily) = daily {
+            json_data.daily_budget = (daily > 0).then_some(daily);
+        }
+        if let Some(minute) = minute {
+            json_data.minute_budget = (minute > 0).then_some(minute);
+        }
+        write_json_file(USER_SETTING_JSON_NAME, &json_data)?;
+        println!("Successfully updated your API budget!");
+    }
+
+    let describe = |budget: Option<u32>| match budget {
+        Some(budget) => format!("{} calls", budget),
+        None => "unlimited".to_string(),
+    };
+    println!("- Daily budget: {}", describe(json_data.daily_budget));
+    println!("- Per-minute budget: {}", describe(json_data.minute_budget));
+
+    Ok(())
+}
+
+/// Settings that can be changed with the `config` command.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ConfigKey {
+    /// Connection timeout in seconds.
+    ConnectTimeout,
+    /// Timeout for receiving the whole response, in seconds.
+    ReadTimeout,
+    /// How many times a failed request is retried.
+    MaxRetries,
+    /// Maximum number of requests per minute from a single run. `0` means unlimited.
+    RateLimit,
+    /// Command printing the API key to stdout.
+    KeyCommand,
+    /// Path of a file containing the API key.
+    KeyFile,
+    /// Proxy URL for every request.
+    Proxy,
+    /// Path of a PEM file with extra root certificates.
+    CaBundle,
+    /// Path of a PEM file with the client certificate chain.
+    ClientCert,
+    /// Path of a PEM file with the PKCS#8 private key of the client certificate.
+    ClientKey,
+    /// Temperature unit. (kelvin, celsius, fahrenheit)
+    TemperatureUnit,
+    /// Wind speed unit. (ms, kmh, mph, knots, beaufort)
+    WindUnit,
+    /// Pressure unit. (hpa, inhg, mmhg, kpa)
+    PressureUnit,
+    /// Precipitation unit. (mm, in)
+    PrecipitationUnit,
+    /// Visibility unit. (m, km, mi)
+    VisibilityUnit,
+    /// Icon style of `check`. (none, nerd-font, emoji, ascii)
+    Icons,
+    /// Color theme of `check`. (default, high-contrast, monochrome, custom)
+    Theme,
+    /// Clock format of sunrise and sunset. (12h, 24h or a strftime pattern like %H:%M)
+    TimeFormat,
+    /// Time zone of sunrise and sunset. (city, local, both)
+    TimeZone,
+    /// Whether to show how long until the upcoming event. (true, false)
+    RelativeTime,
+}
+
+/// Updates a setting. `None` restores the default value.
+pub fn set_config_value(key: ConfigKey, value: Option<&str>) -> Result<()> {
+    use crate::{
+        constants::USER_SETTING_JSON_NAME, read_json_file, types::user_settings::ApiSetting,
+        write_json_file, write_private_json_file,
+    };
+
+    fn parse<T: std::str::FromStr>(value: Option<&str>) -> Result<Option<T>> {
+        value
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<T>()
+                    .map_err(|_| anyhow!("Invalid value: {}", value))
+            })
+            .transpose()
+    }
+
+    fn parse_value_enum<T: clap::ValueEnum>(value: Option<&str>) -> Result<Option<T>> {
+        value
+            .map(|value| {
+                T::from_str(value.trim(), true).map_err(|_| {
+                    let possible_values: Vec<String> = T::value_variants()
+                        .iter()
+                        .filter_map(|variant| variant.to_possible_value())
+                        .map(|possible_value| possible_value.get_name().to_string())
+                        .collect();
+                    anyhow!(
+                        "Invalid value: {} (possible values: {})",
+                        value,
+                        possible_values.join(", ")
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    // The key source is in the API key file, so only that file is updated.
+    fn update_api_setting(update: impl FnOnce(&mut ApiSetting)) -> Result<()> {
+        let mut api_setting = read_json_file::<ApiSetting>(API_JSON_NAME)?;
+        update(&mut api_setting);
+        write_private_json_file(API_JSON_NAME, &api_setting)?;
+        println!("Successfully updated your settings!");
+        Ok(())
+    }
+
+    let mut json_data = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    let network = &mut json_data.network;
+    let display_units = &mut json_data.display_units;
+
+    match key {
+        ConfigKey::ConnectTimeout => network.connect_timeout = parse(value)?,
+        ConfigKey::ReadTimeout => network.read_timeout = parse(value)?,
+        ConfigKey::MaxRetries => network.max_retries = parse(value)?,
+        ConfigKey::RateLimit => network.rate_limit = parse(value)?,
+        ConfigKey::KeyCommand => {
+            return update_api_setting(|api_setting| {
+                api_setting.key_command = value.map(str::to_string)
+            })
+        }
+        ConfigKey::KeyFile => {
+            return update_api_setting(|api_setting| {
+                api_setting.key_file = value.map(str::to_string)
+            })
+        }
+        ConfigKey::Proxy => network.proxy = value.map(str::to_string),
+        ConfigKey::CaBundle => network.ca_bundle = value.map(str::to_string),
+        ConfigKey::ClientCert => network.client_cert = value.map(str::to_string),
+        ConfigKey::ClientKey => network.client_key = value.map(str::to_string),
+        ConfigKey::TemperatureUnit => display_units.temperature = parse_value_enum(value)?,
+        ConfigKey::WindUnit => display_units.wind = parse_value_enum(value)?,
+        ConfigKey::PressureUnit => display_units.pressure = parse_value_enum(value)?,
+        ConfigKey::PrecipitationUnit => display_units.precipitation = parse_value_enum(value)?,
+        ConfigKey::VisibilityUnit => display_units.visibility = parse_value_enum(value)?,
+        ConfigKey::Icons => json_data.icons = parse_value_enum(value)?,
+        ConfigKey::Theme => json_data.theme = parse_value_enum(value)?,
+        ConfigKey::TimeFormat => {
+            if let Some(format) = value {
+                crate::time_display::validate_clock_format(format)?;
+            }
+            json_data.time.format = value.map(str::to_string);
+        }
+        ConfigKey::TimeZone => json_data.time.zone = parse_value_enum(value)?,
+        ConfigKey::RelativeTime => json_data.time.relative = parse(value)?,
+    }
+
+    // Fails early on an invalid theme file.
+    crate::theme::Theme::load(json_data.theme.unwrap_or_default())?;
+
+    // Fails early on an invalid proxy URL or unreadable certificate files.
+    // A client certificate is checked once both of its files are set.
+    if network.client_cert.is_some() == network.client_key.is_some() {
+        crate::http::build_client(network)?;
+    }
+
+    write_json_file(USER_SETTING_JSON_NAME, &json_data)?;
+    println!("Successfully updated your settings!");
+
+    Ok(())
+}
+
+/// Prints the current settings and where the API key is read from.
+pub fn print_config() -> Result<()> {
+    use crate::{
+        api_key::{describe_primary_key, environment_api_key, mask_api_key, ApiKeySource},
+        constants::{
+            DEFAULT_CACHE_TTL_SECONDS, DEFAULT_CONNECT_TIMEOUT_SECONDS, DEFAULT_MAX_RETRIES,
+            DEFAULT_RATE_LIMIT_PER_MINUTE, DEFAULT_READ_TIMEOUT_SECONDS, USER_SETTING_JSON_NAME,
+        },
+        get_json_file_path,
+        http::redact_proxy_url,
+        read_json_file,
+        types::user_settings::{ApiSetting, Units},
+        units::UnitSystem,
+    };
+
+    let json_data = read_json_file::<UserSetting>(USER_SETTING_JSON_NAME)?;
+    let with_default = |value: Option<String>, default: String| match value {
+        Some(value) => value,
+        None => format!("{} (default)", default),
+    };
+    let with_limit = |budget: Option<u32>| match budget {
+        Some(budget) => format!("{} calls", budget),
+        None => "unlimited".to_string(),
+    };
+
+    println!("\n* Files");
+    println!(
+        "- Settings: {}",
+        get_json_file_path(USER_SETTING_JSON_NAME)?
+    );
+    println!("- API key: {}", get_json_file_path(API_JSON_NAME)?);
+
+    // The key isn't resolved, so no key command runs and no passphrase is asked.
+    println!("\n* API key");
+    let api_setting = read_json_file::<ApiSetting>(API_JSON_NAME).unwrap_or_default();
+    match (environment_api_key(), describe_primary_key(&api_setting)) {
+        (Some(key), _) => println!(
+            "- Source: {} ({})",
+            ApiKeySource::Environment,
+            mask_api_key(&key)
+        ),
+        (None, Some(primary_source)) => println!("- Source: {}", primary_source),
+        (None, None) if !api_setting.keys.is_empty() => println!("- Source: named keys"),
+        (None, None) => println!("- Source: none"),
+    }
+    if !api_setting.keys.is_empty() {
+        println!(
+            "- Named keys: {} (see 'setup-api --list')",
+            api_setting.keys.len()
+        );
+    }
+
+    println!("\n* Location");
+    match &json_data.city {
+        Some(city) => println!("- City: {}", city),
+        None => println!("- City: not set"),
+    }
+    match &json_data.units {
+        Some(units) => println!("- Units: {}", units),
+        None => println!("- Units: not set"),
+    }
+
+    let display_units = &json_data.display_units;
+    let unit_system = UnitSystem::new(
+        json_data.units.as_ref().unwrap_or(&Units::Standard),
+        display_units,
+    );
+    println!("\n* Display units");
+    for (name, configured, symbol) in [
+        (
+            "temperature-unit",
+            display_units.temperature.is_some(),
+            unit_system.temperature.symbol(),
+        ),
+        (
+            "wind-unit",
+            display_units.wind.is_some(),
+            unit_system.wind.symbol(),
+        ),
+        (
+            "pressure-unit",
+            display_units.pressure.is_some(),
+            unit_system.pressure.symbol(),
+        ),
+        (
+            "precipitation-unit",
+            display_units.precipitation.is_some(),
+            unit_system.precipitation.symbol(),
+        ),
+        (
+            "visibility-unit",
+            display_units.visibility.is_some(),
+            unit_system.visibility.symbol(),
+        ),
+    ] {
+        match configured {
+            true => println!("- {}: {}", name, symbol),
+            false => println!("- {}: {} (default)", name, symbol),
+        }
+    }
+
+    println!("\n* Display");
+    match json_data.icons {
+        Some(icons) => println!("- icons: {}", icons),
+        None => println!("- icons: none (default)"),
+    }
+    match json_data.theme {
+        Some(theme) => println!("- theme: {}", theme),
+        None => println!("- theme: default (default)"),
+    }
+    println!(
+        "- time-format: {}",
+        with_default(json_data.time.format.clone(), "12h".to_string())
+    );
+    println!(
+        "- time-zone: {}",
+        with_default(
+            json_data.time.zone.map(|zone| zone.to_string()),
+            "city".to_string()
+        )
+    );
+    println!(
+        "- relative-time: {}",
+        with_default(
+            json_data.time.relative.map(|relative| relative.to_string()),
+            "false".to_string()
+        )
+    );
+
+    println!("\n* Cache and usage");
+    println!(
+        "- Cache TTL: {} seconds",
+        with_default(
+            json_data.cache_ttl.map(|ttl| ttl.to_string()),
+            DEFAULT_CACHE_TTL_SECONDS.to_string()
+        )
+    );
+    println!("- Daily budget: {}", with_limit(json_data.daily_budget));
+    println!(
+        "- Per-minute budget: {}",
+        with_limit(json_data.minute_budget)
+    );
+
+    let network = &json_data.network;
+    println!("\n* Network");
+    println!(
+        "- connect-timeout: {}",
+        with_default(
+            network.connect_timeout.map(|value| value.to_string()),
+            DEFAULT_CONNECT_TIMEOUT_SECONDS.to_string()
+        )
+    );
+    println!(
+        "- read-timeout: {}",
+        with_default(
+            network.read_timeout.map(|value| value.to_string()),
+            DEFAULT_READ_TIMEOUT_SECONDS.to_string()
+        )
+    );
+    println!(
+        "- max-retries: {}",
+        with_default(
+            network.max_retries.map(|value| value.to_string()),
+            DEFAULT_MAX_RETRIES.to_string()
+        )
+    );
+    println!(
+        "- rate-limit: {}",
+        with_default(
+            network.rate_limit.map(|value| match value {
+                0 => "unlimited".to_string(),
+                value => value.to_string(),
+            }),
+            DEFAULT_RATE_LIMIT_PER_MINUTE.to_string()
+        )
+    );
+    println!(
+        "- proxy: {}",
+        with_default(
+            network.proxy.as_deref().map(redact_proxy_url),
+            "proxy environment variables".to_string()
+        )
+    );
+    let with_none = |path: &Option<String>| path.clone().unwrap_or_else(|| "none".to_string());
+    println!("- ca-bundle: {}", with_none(&network.ca_bundle));
+    println!("- client-cert: {}", with_none(&network.client_cert));
+    println!("- client-key: {}", with_none(&network.client_key));
+
+    Ok(())
+}